@@ -0,0 +1,133 @@
+use rand::{rngs::StdRng, Rng};
+use std::{f64::consts::E, fmt};
+
+
+/// Decides whether a move of the search is accepted, all values are working objectives (including penalties)
+pub trait AcceptanceCriterion: fmt::Debug + Send {
+    /// `candidate` is the objective after the move, `delta` its change
+    fn accept(&mut self, delta: f64, candidate: f64, temperature: f64, rng: &mut StdRng) -> bool;
+
+    /// Called after every decision with the objective of the current state and the progress of the schedule in [0, 1]
+    fn update(&mut self, _current: f64, _progress: f64) {}
+
+    /// Starts over from the current objective, when the objectives seen so far are not comparable anymore (other
+    /// penalty weights) or the search continues from another state (restart)
+    fn reset(&mut self, _current: f64) {}
+}
+
+/// Available acceptance criteria
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Acceptance {
+    Metropolis,
+    LateAcceptance { length: usize },   // Compare against the objective `length` iterations ago
+    ThresholdAccepting,                 // Accept worsenings below the temperature
+    GreatDeluge,                        // Accept below a level that sinks from the initial to the best objective
+    RecordToRecord { deviation: f64 }   // Accept within a relative deviation from the best objective
+}
+
+#[derive(Debug)]
+struct Metropolis;
+
+#[derive(Debug)]
+struct LateAcceptance {
+    history: Vec<f64>,  // Empty until the first decision
+    length: usize,
+    position: usize
+}
+
+#[derive(Debug)]
+struct ThresholdAccepting;
+
+#[derive(Debug)]
+struct GreatDeluge {
+    initial: Option<f64>,
+    best: f64,
+    level: f64
+}
+
+#[derive(Debug)]
+struct RecordToRecord {
+    deviation: f64,
+    record: f64
+}
+
+impl Acceptance {
+    pub fn build(&self) -> Box<dyn AcceptanceCriterion> {
+        match *self {
+            Acceptance::Metropolis => Box::new(Metropolis),
+            Acceptance::LateAcceptance { length } => Box::new(LateAcceptance { history: Vec::new(), length: length.max(1), position: 0 }),
+            Acceptance::ThresholdAccepting => Box::new(ThresholdAccepting),
+            Acceptance::GreatDeluge => Box::new(GreatDeluge { initial: None, best: f64::MAX, level: f64::MAX }),
+            Acceptance::RecordToRecord { deviation } => Box::new(RecordToRecord { deviation, record: f64::MAX })
+        }
+    }
+}
+
+impl AcceptanceCriterion for Metropolis {
+    fn accept(&mut self, delta: f64, _: f64, temperature: f64, rng: &mut StdRng) -> bool {
+        if delta <= 0f64 {
+            return true;
+        }
+        // high delta = bad move = x small
+        rng.gen::<f64>() < E.powf(- delta / temperature)
+    }
+}
+
+impl AcceptanceCriterion for LateAcceptance {
+    fn accept(&mut self, delta: f64, candidate: f64, _: f64, _: &mut StdRng) -> bool {
+        if self.history.is_empty() {
+            self.history = vec![candidate - delta; self.length];
+        }
+        delta <= 0.0 || candidate <= self.history[self.position]
+    }
+
+    fn update(&mut self, current: f64, _: f64) {
+        if self.history.is_empty() { return; }
+        self.history[self.position] = current;
+        self.position = (self.position + 1) % self.length;
+    }
+
+    fn reset(&mut self, current: f64) {
+        self.history = vec![current; self.length];
+        self.position = 0;
+    }
+}
+
+impl AcceptanceCriterion for ThresholdAccepting {
+    fn accept(&mut self, delta: f64, _: f64, temperature: f64, _: &mut StdRng) -> bool {
+        delta < temperature
+    }
+}
+
+impl AcceptanceCriterion for GreatDeluge {
+    fn accept(&mut self, delta: f64, candidate: f64, _: f64, _: &mut StdRng) -> bool {
+        delta <= 0.0 || candidate <= self.level
+    }
+
+    fn update(&mut self, current: f64, progress: f64) {
+        let initial = *self.initial.get_or_insert(current);
+        self.best = self.best.min(current);
+        self.level = self.best + (initial - self.best) * (1.0 - progress);
+    }
+
+    // The level sinks from the current objective for the rest of the schedule
+    fn reset(&mut self, current: f64) {
+        self.initial = Some(current);
+        self.best = current;
+        self.level = current;
+    }
+}
+
+impl AcceptanceCriterion for RecordToRecord {
+    fn accept(&mut self, delta: f64, candidate: f64, _: f64, _: &mut StdRng) -> bool {
+        delta <= 0.0 || candidate <= self.record * (1.0 + self.deviation)
+    }
+
+    fn update(&mut self, current: f64, _: f64) {
+        self.record = self.record.min(current);
+    }
+
+    fn reset(&mut self, current: f64) {
+        self.record = current;
+    }
+}
@@ -0,0 +1,103 @@
+use rand::{distributions::WeightedIndex, prelude::*, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+
+/// Result of applying an operator, used to score it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveOutcome {
+    Rejected,
+    Accepted,   // Accepted, but not better than the current state
+    Improved,   // Better than the current state
+    NewBest     // Better than the best state so far
+}
+
+/// Parameters of the segment-wise weight adaption (roulette wheel selection as in ALNS)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AdaptionParameters {
+    pub segment_length: usize,
+    pub reaction: f64,  // 0 = keep weights, 1 = only use the scores of the last segment
+    pub new_best_score: f64,
+    pub improvement_score: f64,
+    pub accept_score: f64,
+    pub min_weight: f64
+}
+
+/// Roulette wheel over a set of operators whose weights follow the scores they earn per segment
+#[derive(Debug, Clone)]
+pub struct AdaptiveWeights {
+    parameters: Option<AdaptionParameters>,   // None = static weights
+    weights: Vec<f64>,
+    scores: Vec<f64>,
+    uses: Vec<usize>,
+    segment_iterations: usize,
+    selection: WeightedIndex<f64>
+}
+
+impl AdaptiveWeights {
+    pub fn new(weights: Vec<f64>, parameters: Option<AdaptionParameters>) -> Self {
+        let selection = WeightedIndex::new(weights.iter()).expect("Invalid operator weights");
+        let num = weights.len();
+
+        AdaptiveWeights {
+            parameters,
+            weights,
+            scores: vec![0.0; num],
+            uses: vec![0; num],
+            segment_iterations: 0,
+            selection
+        }
+    }
+
+    pub fn select(&self, rng: &mut StdRng) -> usize {
+        self.selection.sample(rng)
+    }
+
+    pub fn record(&mut self, idx: usize, outcome: MoveOutcome) {
+        let parameters = match &self.parameters {
+            Some(parameters) => parameters,
+            None => return
+        };
+        self.scores[idx] += match outcome {
+            MoveOutcome::Rejected => 0.0,
+            MoveOutcome::Accepted => parameters.accept_score,
+            MoveOutcome::Improved => parameters.improvement_score,
+            MoveOutcome::NewBest => parameters.new_best_score
+        };
+        self.uses[idx] += 1;
+        self.segment_iterations += 1;
+        if self.segment_iterations >= parameters.segment_length {
+            self.update_weights();
+        }
+    }
+
+    // End of segment: move weights towards the average score of each used operator
+    fn update_weights(&mut self) {
+        let parameters = self.parameters.as_ref().unwrap();
+        for i in 0..self.weights.len() {
+            if self.uses[i] == 0 { continue; }
+            let avg_score = self.scores[i] / self.uses[i] as f64;
+            self.weights[i] = ((1.0 - parameters.reaction) * self.weights[i] + parameters.reaction * avg_score).max(parameters.min_weight);
+        }
+        self.selection = WeightedIndex::new(self.weights.iter()).unwrap();
+        self.scores.iter_mut().for_each(|score| *score = 0.0);
+        self.uses.iter_mut().for_each(|uses| *uses = 0);
+        self.segment_iterations = 0;
+    }
+
+    pub fn weights(&self) -> &Vec<f64> {
+        &self.weights
+    }
+}
+
+impl Default for AdaptionParameters {
+    fn default() -> Self {
+        AdaptionParameters {
+            segment_length: 1000,
+            reaction: 0.1,
+            new_best_score: 33.0,
+            improvement_score: 9.0,
+            accept_score: 13.0,
+            min_weight: 1.0
+        }
+    }
+}
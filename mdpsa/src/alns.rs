@@ -0,0 +1,392 @@
+use rand::{prelude::*, rngs::StdRng};
+use std::{fmt, time::Instant};
+
+use super::{acceptance::AcceptanceCriterion, adaptive_weights::{AdaptionParameters, AdaptiveWeights, MoveOutcome}, cooling::CoolingSchedule, neighborhood::{ChangeToken, OperatorStats}, simulated_annealing::{SAParameters, Termination}, state::{JobToken, State}};
+
+
+/// Parameters of the adaptive large neighborhood search, temperatures, cooling and acceptance are taken from the annealing
+/// (without its reheats, restarts and penalty adaption)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ALNSParameters {
+    pub min_removal: f64,   // Fraction of the tasks (or of the horizon) removed by a destroy operator
+    pub max_removal: f64,
+    pub regret: usize,      // k of the regret repair
+    pub randomization: f64  // Bias of the randomized greedy repair towards cheap insertions (1 = uniform)
+}
+
+/// Removes part of the schedule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Destroy {
+    Resource,       // All jobs of a random resource
+    TimeWindow,     // All jobs overlapping a random time window on any resource
+    Related,        // A random task and the tasks closest to it in time
+    WorstCoverage   // Tasks that end the longest after the last maintenance before them
+}
+
+/// Rebuilds the schedule after a destroy operator: major maintenances first, then tasks, then coverage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repair {
+    Greedy,                 // State::repair
+    Regret(usize),          // Insert the task with the highest regret of not using its k best resources first
+    RandomizedGreedy(f64)   // Tasks in random order, on a resource drawn with a bias towards the cheapest
+}
+
+/// ALNS: destroy and repair operators are drawn by adaptive weights, the result is accepted like an annealing move
+pub struct AdaptiveLargeNeighborhoodSearch {
+    parameters: ALNSParameters,
+    annealing: SAParameters,
+    schedule: Box<dyn CoolingSchedule>,
+    acceptance: Box<dyn AcceptanceCriterion>,
+    temperature: f64,
+    progress: f64,
+    state: State,
+    rng: StdRng,
+    destroy: Vec<Destroy>,
+    repair: Vec<Repair>,
+    destroy_selection: AdaptiveWeights,
+    repair_selection: AdaptiveWeights,
+    stats: Vec<OperatorStats>,  // Destroy operators followed by repair operators
+    iterations: usize,
+    iterations_since_accept: usize,
+    iterations_since_improvement: usize,
+    best_obj: usize,    // Best working objective (including penalties)
+    best_feasible: Option<(usize, State)>
+}
+
+impl AdaptiveLargeNeighborhoodSearch {
+    pub fn new(state: State, rng: StdRng, parameters: ALNSParameters, annealing: SAParameters) -> Self {
+        let destroy = vec![Destroy::Resource, Destroy::TimeWindow, Destroy::Related, Destroy::WorstCoverage];
+        let repair = vec![Repair::Greedy, Repair::Regret(parameters.regret), Repair::RandomizedGreedy(parameters.randomization)];
+        let stats = destroy.iter().map(|op| op.to_string()).chain(repair.iter().map(|op| op.to_string())).map(OperatorStats::new).collect();
+        // Segments are much shorter than for single moves, every iteration is a large move
+        let adaption = AdaptionParameters { segment_length: 100, ..AdaptionParameters::default() };
+        let destroy_selection = AdaptiveWeights::new(vec![1.0; destroy.len()], Some(adaption.clone()));
+        let repair_selection = AdaptiveWeights::new(vec![1.0; repair.len()], Some(adaption));
+        let best_feasible = state.initial_best();
+
+        AdaptiveLargeNeighborhoodSearch {
+            parameters,
+            schedule: annealing.cooling().build(),
+            acceptance: annealing.acceptance().build(),
+            temperature: annealing.initial_temperature(),
+            annealing,
+            progress: 0.0,
+            state,
+            rng,
+            destroy,
+            repair,
+            destroy_selection,
+            repair_selection,
+            stats,
+            iterations: 0,
+            iterations_since_accept: 0,
+            iterations_since_improvement: 0,
+            best_obj: usize::MAX,
+            best_feasible
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn get_best(&self) -> &Option<(usize, State)> {
+        &self.best_feasible
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn iterations_since_accept(&self) -> usize {
+        self.iterations_since_accept
+    }
+
+    pub fn iterations_since_improvement(&self) -> usize {
+        self.iterations_since_improvement
+    }
+
+    /// Name and current selection weight of each destroy and repair operator
+    pub fn operator_weights(&self) -> Vec<(String, f64)> {
+        let names = self.stats.iter().map(|stats| stats.name().to_string());
+        let weights = self.destroy_selection.weights().iter().chain(self.repair_selection.weights().iter());
+        names.zip(weights).map(|(name, weight)| (name, *weight)).collect()
+    }
+
+    /// Call and outcome counters of each destroy and repair operator
+    pub fn operator_stats(&self) -> &Vec<OperatorStats> {
+        &self.stats
+    }
+
+    pub fn solve(&mut self, termination: Termination) -> usize {
+        let start = Instant::now();
+        loop {
+            if termination.is_reached(self.iterations) { break; }
+            let now = Instant::now();
+            let progress = match termination {
+                Termination::Iterations(max_iterations) => self.iterations as f64 / max_iterations as f64,
+                Termination::Deadline(deadline) => (now.duration_since(start).as_secs_f64() / deadline.duration_since(start).as_secs_f64()).min(1.0)
+            };
+            self.progress = progress;
+            self.temperature = self.schedule.temperature(self.annealing.initial_temperature(), self.annealing.final_temperature(),
+                progress, termination.schedule_time(progress, now.duration_since(start), self.annealing.iteration_clock()));
+            self.step();
+        }
+
+        self.iterations
+    }
+
+    // Destroy and repair, accept or undo both
+    fn step(&mut self) {
+        if self.annealing.verify_every() != 0 && self.iterations.is_multiple_of(self.annealing.verify_every()) {
+            let working_penalty = self.state.working_obj_val() - self.state.obj_value();
+            assert_eq!(working_penalty, self.state.calc_penalty_from_scratch());
+            assert!(self.state.is_feasible(false));
+        }
+        self.iterations += 1;
+        self.iterations_since_improvement += 1;
+
+        let destroy = self.destroy_selection.select(&mut self.rng);
+        let repair = self.repair_selection.select(&mut self.rng);
+        let obj_prev = self.state.working_obj_val();
+
+        let timer = Instant::now();
+        let fraction = self.rng.gen_range(self.parameters.min_removal..=self.parameters.max_removal.max(self.parameters.min_removal));
+        let mut changes = self.destroy[destroy].destroy(&mut self.state, fraction, &mut self.rng);
+        let obj_destroyed = self.state.working_obj_val();
+        self.stats[destroy].record_call(obj_destroyed as f64 - obj_prev as f64, changes.is_empty(), timer.elapsed());
+
+        let timer = Instant::now();
+        let mut repaired = self.repair[repair].repair(&mut self.state, &mut self.rng);
+        let obj_val = self.state.working_obj_val();
+        self.stats[self.destroy.len() + repair].record_call(obj_val as f64 - obj_destroyed as f64, repaired.is_empty(), timer.elapsed());
+        changes.append(&mut repaired);
+
+        let delta = obj_val as f64 - obj_prev as f64;
+        let outcome = if !self.acceptance.accept(delta, obj_val as f64, self.temperature, &mut self.rng) {
+            self.state.undo_changes(&changes);
+            self.iterations_since_accept += 1;
+            MoveOutcome::Rejected
+        } else if obj_val < self.best_obj {
+            MoveOutcome::NewBest
+        } else if delta < 0.0 {
+            MoveOutcome::Improved
+        } else {
+            MoveOutcome::Accepted
+        };
+        // Moves that changed nothing earn no score
+        let outcome = if changes.is_empty() { MoveOutcome::Rejected } else { outcome };
+        self.destroy_selection.record(destroy, outcome);
+        self.repair_selection.record(repair, outcome);
+        self.stats[destroy].record_outcome(outcome);
+        self.stats[self.destroy.len() + repair].record_outcome(outcome);
+        self.acceptance.update(self.state.working_obj_val() as f64, self.progress);
+        if outcome == MoveOutcome::Rejected { return; }
+
+        if delta != 0.0 {
+            self.iterations_since_accept = 0;
+        }
+        if obj_val < self.best_obj {
+            self.best_obj = obj_val;
+            self.iterations_since_improvement = 0;
+        }
+        self.state.update_best(&mut self.best_feasible);
+    }
+}
+
+impl Destroy {
+    /// Removes jobs from the state, `fraction` is the share of the tasks (or of the horizon) to remove
+    pub fn destroy(&self, state: &mut State, fraction: f64, rng: &mut StdRng) -> Vec<ChangeToken> {
+        let removals = ((fraction * state.instance().tasks().len() as f64).round() as usize).max(1);
+        match self {
+            Destroy::Resource => {
+                let res = rng.gen_range(0..state.instance().resources());
+                let jobs = state.jobs()[res].iter().map(|(time, job)| (*time, job.clone())).collect::<Vec<_>>();
+                jobs.into_iter().map(|(time, job)| remove_job(state, res, time, &job)).collect()
+            },
+            Destroy::TimeWindow => {
+                let horizon = state.instance().horizon();
+                let length = ((fraction * horizon as f64) as usize).clamp(1, horizon);
+                let start = rng.gen_range(0..horizon - length + 1);
+                let mut change_tokens = Vec::new();
+                for res in 0..state.instance().resources() {
+                    for (time, job) in state.get_overlaps(res, start, start + length) {
+                        change_tokens.push(remove_job(state, res, time, &job));
+                    }
+                }
+                change_tokens
+            },
+            Destroy::Related => {
+                let (_, seed) = match state.get_rand_assigned_task(rng) {
+                    Some(task) => task,
+                    None => return Vec::new()
+                };
+                // Distance between the task intervals, negative if they overlap
+                let seed = &state.instance().tasks()[seed];
+                let distance = |task_id: usize| {
+                    let task = &state.instance().tasks()[task_id];
+                    task.start().max(seed.start()) as isize - task.end().min(seed.end()) as isize
+                };
+                let mut tasks = assigned_tasks(state);
+                tasks.sort_by_key(|task_id| distance(*task_id));
+                remove_tasks(state, &tasks[..removals.min(tasks.len())])
+            },
+            Destroy::WorstCoverage => {
+                // Time from the end of the last maintenance before a task to the end of the task
+                let gap = |task_id: usize| {
+                    let task = &state.instance().tasks()[task_id];
+                    let last_maint = state.jobs()[state.task_ass()[task_id]].range(..task.start()+1).rev()
+                        .find(|(_, job)| !matches!(job, JobToken::Task(_)))
+                        .map_or(0, |(time, _)| *time);
+                    task.end() - last_maint
+                };
+                let mut tasks = assigned_tasks(state);
+                tasks.sort_by_key(|task_id| std::cmp::Reverse(gap(*task_id)));
+                remove_tasks(state, &tasks[..removals.min(tasks.len())])
+            }
+        }
+    }
+}
+
+impl Repair {
+    pub fn repair(&self, state: &mut State, rng: &mut StdRng) -> Vec<ChangeToken> {
+        match *self {
+            Repair::Greedy => state.repair(rng),
+            Repair::Regret(k) => {
+                let mut change_tokens = state.repair_maj_maints(rng);
+                change_tokens.append(&mut regret_insertion(state, k));
+                change_tokens.append(&mut state.repair_coverage(rng));
+                change_tokens
+            },
+            Repair::RandomizedGreedy(randomization) => {
+                let mut change_tokens = state.repair_maj_maints(rng);
+                let mut tasks = (0..state.instance().tasks().len()).filter(|task_id| !state.assigned_tasks()[*task_id]).collect::<Vec<_>>();
+                tasks.shuffle(rng);
+                for task_id in tasks.into_iter() {
+                    let mut costs = insertion_costs(state, task_id).into_iter().enumerate()
+                        .filter_map(|(res, cost)| cost.map(|cost| (cost, res))).collect::<Vec<_>>();
+                    if costs.is_empty() { continue; }
+                    costs.sort();
+                    let (_, res) = costs[(rng.gen::<f64>().powf(randomization) * costs.len() as f64) as usize];
+                    state.add_task(res, task_id);
+                    change_tokens.push(ChangeToken::AddTask(task_id));
+                }
+                change_tokens.append(&mut state.repair_coverage(rng));
+                change_tokens
+            }
+        }
+    }
+}
+
+fn remove_job(state: &mut State, res: usize, time: usize, job: &JobToken) -> ChangeToken {
+    match job {
+        JobToken::MajMaint => {
+            state.remove_major_maintenance(res);
+            ChangeToken::RemoveMM(res, time)
+        },
+        JobToken::RegMaint => {
+            state.remove_regular_maintenance(res, time);
+            ChangeToken::RemoveRM(res, time)
+        },
+        JobToken::Task(id) => {
+            state.remove_task(*id);
+            ChangeToken::RemoveTask(res, *id)
+        }
+    }
+}
+
+fn remove_tasks(state: &mut State, tasks: &[usize]) -> Vec<ChangeToken> {
+    tasks.iter().map(|task_id| {
+        let res = state.task_ass()[*task_id];
+        state.remove_task(*task_id);
+        ChangeToken::RemoveTask(res, *task_id)
+    }).collect()
+}
+
+fn assigned_tasks(state: &State) -> Vec<usize> {
+    (0..state.instance().tasks().len()).filter(|task_id| state.assigned_tasks()[*task_id]).collect()
+}
+
+// Change of the working objective when adding the task to each resource, None if it does not fit
+fn insertion_costs(state: &mut State, task_id: usize) -> Vec<Option<isize>> {
+    (0..state.instance().resources()).map(|res| insertion_cost(state, res, task_id)).collect()
+}
+
+fn insertion_cost(state: &mut State, res: usize, task_id: usize) -> Option<isize> {
+    if !state.can_add_task(res, task_id) {
+        return None;
+    }
+    let obj_prev = state.working_obj_val();
+    state.add_task(res, task_id);
+    let cost = state.working_obj_val() as isize - obj_prev as isize;
+    state.remove_task(task_id);
+
+    Some(cost)
+}
+
+// Inserts all tasks that fit, the one with the highest regret first (ties by cost), k = 1 is cheapest insertion
+fn regret_insertion(state: &mut State, k: usize) -> Vec<ChangeToken> {
+    let mut change_tokens = Vec::new();
+    let mut tasks = (0..state.instance().tasks().len()).filter(|task_id| !state.assigned_tasks()[*task_id]).collect::<Vec<_>>();
+    let mut costs = tasks.iter().map(|task_id| insertion_costs(state, *task_id)).collect::<Vec<_>>();
+    loop {
+        let mut best: Option<(usize, f64, isize, usize)> = None;    // (idx, regret, cost, res)
+        for (idx, task_costs) in costs.iter().enumerate() {
+            let mut feasible = task_costs.iter().enumerate().filter_map(|(res, cost)| cost.map(|cost| (cost, res))).collect::<Vec<_>>();
+            if feasible.is_empty() { continue; }
+            feasible.sort();
+            let (cost, res) = feasible[0];
+            // Missing alternatives are infinitely expensive, such tasks are inserted first
+            let regret = (1..k).map(|i| feasible.get(i).map_or(f64::INFINITY, |(other, _)| (other - cost) as f64)).sum::<f64>();
+            let better = match best {
+                Some((_, best_regret, best_cost, _)) => regret > best_regret || (regret == best_regret && cost < best_cost),
+                None => true
+            };
+            if better {
+                best = Some((idx, regret, cost, res));
+            }
+        }
+
+        let (idx, _, _, res) = match best {
+            Some(best) => best,
+            None => break
+        };
+        let task_id = tasks.swap_remove(idx);
+        costs.swap_remove(idx);
+        state.add_task(res, task_id);
+        change_tokens.push(ChangeToken::AddTask(task_id));
+        // Only the costs on the changed resource are affected
+        for (task_id, task_costs) in tasks.iter().zip(costs.iter_mut()) {
+            task_costs[res] = insertion_cost(state, res, *task_id);
+        }
+    }
+
+    change_tokens
+}
+
+impl fmt::Display for Destroy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Destroy::Resource => write!(f, "Destroy resource"),
+            Destroy::TimeWindow => write!(f, "Destroy time window"),
+            Destroy::Related => write!(f, "Destroy related"),
+            Destroy::WorstCoverage => write!(f, "Destroy worst coverage")
+        }
+    }
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Repair::Greedy => write!(f, "Repair greedy"),
+            Repair::Regret(k) => write!(f, "Repair regret-{}", k),
+            Repair::RandomizedGreedy(_) => write!(f, "Repair randomized greedy")
+        }
+    }
+}
+
+impl Default for ALNSParameters {
+    fn default() -> Self {
+        ALNSParameters { min_removal: 0.05, max_removal: 0.2, regret: 3, randomization: 3.0 }
+    }
+}
@@ -0,0 +1,441 @@
+use super::{instance::Instance, neighborhood::PenaltyWeights, simulated_annealing::Termination, solution::Solution, state::State, validator};
+
+
+/// Parameters of the exact solver
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBParameters {
+    pub seed_iterations: usize  // Iterations of the annealing run that seeds the incumbent (0 = no seeding)
+}
+
+/// Depth-first branch and bound for small instances.
+///
+/// The objective (sum over time of the squared number of running maintenances) is the total maintenance time plus
+/// twice the pairwise overlaps of the maintenances. The search branches over the task assignments first, bounded by
+/// `resources * duration_major + duration_regular * (regular maintenances)`: the regular maintenances needed per
+/// resource are the fewest maintenance ends that hit the coverage window [end - time_regular, start] of each of its
+/// tasks, fitting between its tasks (the major maintenance may be one of them). At a leaf it branches over the
+/// maintenance placements, the gaps between the tasks holding the maintenances of each resource and then their end
+/// times by splitting the ranges, bounded by the smallest overlap each pair of maintenances can have. Resources are
+/// interchangeable, a task is only ever assigned to one of the used resources or the first unused one.
+pub struct BranchAndBound {
+    instance: Instance,
+    order: Vec<usize>,  // Tasks by start time
+    assignment: Vec<Vec<usize>>,    // Tasks on each resource, by start time
+    bounds: Vec<usize>,     // Regular maintenances needed per resource
+    nodes: usize,
+    improved_at: usize,     // Nodes when the incumbent last improved
+    aborted: bool,
+    open_bound: usize,  // Smallest bound of the subtrees not explored
+    best_feasible: Option<(usize, State)>
+}
+
+// Maintenances hitting the coverage windows up to a task, the major maintenance may be one of them
+#[derive(Debug, Clone)]
+struct Cover {
+    ends: Vec<usize>,   // Ends of the regular maintenances
+    major: Option<usize>
+}
+
+// Maintenance of a leaf with the range of its end time
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    res: usize,
+    major: bool,
+    length: usize,
+    earliest: usize,
+    latest: usize,
+    follows: bool   // Starts after the end of the previous placement (major and regular maintenance sharing a gap)
+}
+
+impl BranchAndBound {
+    /// `incumbent` is a known solution (e.g. of an annealing run), subtrees that cannot beat it are pruned
+    pub fn new(instance: Instance, incumbent: Option<(usize, State)>) -> Self {
+        let mut order = (0..instance.tasks().len()).collect::<Vec<usize>>();
+        order.sort_by_key(|task_id| (instance.tasks()[*task_id].start(), instance.tasks()[*task_id].end()));
+        let resources = instance.resources();
+
+        BranchAndBound {
+            instance,
+            order,
+            assignment: vec![Vec::new(); resources],
+            bounds: vec![0; resources],
+            nodes: 0,
+            improved_at: 0,
+            aborted: false,
+            open_bound: usize::MAX,
+            best_feasible: incumbent
+        }
+    }
+
+    pub fn get_best(&self) -> &Option<(usize, State)> {
+        &self.best_feasible
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn nodes_since_improvement(&self) -> usize {
+        self.nodes - self.improved_at
+    }
+
+    /// Proven lower bound of the objective (after `solve`), `None` if the instance has no solution
+    pub fn lower_bound(&self) -> Option<usize> {
+        let incumbent = self.best_feasible.as_ref().map_or(usize::MAX, |(obj, _)| *obj);
+        Some(incumbent.min(self.open_bound)).filter(|bound| *bound != usize::MAX)
+    }
+
+    /// Whether the search completed and the best solution is optimal
+    pub fn is_optimal(&self) -> bool {
+        !self.aborted && self.best_feasible.is_some()
+    }
+
+    /// Searches until all nodes are explored or the termination (iterations = nodes) is reached, returns the nodes
+    pub fn solve(&mut self, termination: Termination) -> usize {
+        let bound = self.bound();
+        self.branch(0, bound, termination);
+
+        self.nodes
+    }
+
+    fn bound(&self) -> usize {
+        self.instance.resources() * self.instance.duration_major() + self.instance.duration_regular() * self.bounds.iter().sum::<usize>()
+    }
+
+    fn incumbent(&self) -> usize {
+        self.best_feasible.as_ref().map_or(usize::MAX, |(obj, _)| *obj)
+    }
+
+    // Stops the search at the termination, the subtree with the given bound stays open
+    fn abort(&mut self, bound: usize, termination: Termination) -> bool {
+        // The clock is only read every 1024 nodes
+        let check = matches!(termination, Termination::Iterations(_)) || self.nodes.is_multiple_of(1024);
+        let done = self.aborted || (check && termination.is_reached(self.nodes));
+        if done {
+            self.aborted = true;
+            self.open_bound = self.open_bound.min(bound);
+        }
+
+        done
+    }
+
+    // Assigns the task at `depth` of the order to every admissible resource, best bound first
+    fn branch(&mut self, depth: usize, bound: usize, termination: Termination) {
+        if self.abort(bound, termination) { return; }
+        self.nodes += 1;
+        if depth == self.order.len() {
+            self.leaf(bound, termination);
+            return;
+        }
+
+        let task_id = self.order[depth];
+        let task = &self.instance.tasks()[task_id];
+        let used = self.assignment.iter().filter(|tasks| !tasks.is_empty()).count();
+        let mut children = Vec::new();
+        for res in 0..(used + 1).min(self.instance.resources()) {
+            let fits = self.assignment[res].last().is_none_or(|last| self.instance.tasks()[*last].end() <= task.start());
+            if !fits { continue; }
+            self.assignment[res].push(task_id);
+            if let Some(needed) = min_cover(&self.instance, &self.assignment[res]).map(|cover| cover.ends.len()) {
+                let child_bound = bound - self.instance.duration_regular() * self.bounds[res] + self.instance.duration_regular() * needed;
+                children.push((child_bound, res, needed));
+            }
+            self.assignment[res].pop();
+        }
+        children.sort();
+
+        for (child_bound, res, needed) in children.into_iter() {
+            if child_bound >= self.incumbent() { break; }   // Sorted, no later child can be better
+            let prev = self.bounds[res];
+            self.assignment[res].push(task_id);
+            self.bounds[res] = needed;
+            self.branch(depth + 1, child_bound, termination);
+            self.assignment[res].pop();
+            self.bounds[res] = prev;
+        }
+    }
+
+    // Chooses the maintenance layout of every resource, then places the maintenances. Layouts with more regular
+    // maintenances than the incumbent leaves room for are not generated.
+    fn leaf(&mut self, bound: usize, termination: Termination) {
+        let extra = self.incumbent().saturating_sub(bound).saturating_sub(1) / self.instance.duration_regular();
+        let layouts = self.assignment.iter().enumerate()
+            .map(|(res, tasks)| layouts(&self.instance, res, tasks, self.bounds[res].saturating_add(extra)))
+            .collect::<Vec<Vec<Vec<Placement>>>>();
+        self.choose(&layouts, &mut Vec::new(), bound, termination);
+    }
+
+    // Adds a layout of the next resource to the placements of the previous ones
+    fn choose(&mut self, layouts: &[Vec<Vec<Placement>>], placements: &mut Vec<Placement>, bound: usize, termination: Termination) {
+        let Some((current, rest)) = layouts.split_first() else {
+            self.place(placements.clone(), bound, termination);
+            return;
+        };
+        let remaining = rest.len() * self.instance.duration_major()
+            + self.instance.duration_regular() * self.bounds[self.instance.resources() - rest.len()..].iter().sum::<usize>();
+        for layout in current.iter() {
+            if self.abort(bound, termination) { return; }
+            self.nodes += 1;
+            let len = placements.len();
+            placements.extend(layout.iter().copied());
+            if let Some((child_bound, _)) = overlap_bound(placements) {
+                if child_bound + remaining < self.incumbent() {
+                    self.choose(rest, placements, child_bound + remaining, termination);
+                }
+            }
+            placements.truncate(len);
+        }
+    }
+
+    // Splits the widest range whose overlaps are not fixed yet, until all are fixed and the earliest ends reach the bound
+    fn place(&mut self, mut placements: Vec<Placement>, bound: usize, termination: Termination) {
+        if self.abort(bound, termination) { return; }
+        self.nodes += 1;
+        if !propagate(&mut placements) { return; }
+        let Some((bound, branch)) = overlap_bound(&placements) else { return; };
+        if bound >= self.incumbent() { return; }
+
+        match branch {
+            None => self.evaluate(&placements),
+            Some(i) => {
+                let mid = (placements[i].earliest + placements[i].latest) / 2;
+                let mut lower = placements.clone();
+                lower[i].latest = mid;
+                placements[i].earliest = mid + 1;
+                self.place(lower, bound, termination);
+                self.place(placements, bound, termination);
+            }
+        }
+    }
+
+    // Schedules the maintenances at their earliest ends
+    fn evaluate(&mut self, placements: &[Placement]) {
+        let mut state = State::new(self.instance.clone(), PenaltyWeights::default());
+        state.add_initial_penalties();
+        for placement in placements.iter() {
+            if placement.major {
+                state.add_major_maintenance(placement.res, placement.earliest);
+            } else {
+                state.add_regular_maintenance(placement.res, placement.earliest);
+            }
+        }
+        for (res, tasks) in self.assignment.iter().enumerate() {
+            for task_id in tasks.iter() {
+                state.add_task(res, *task_id);
+            }
+        }
+
+        let feasible = validator::validate(&self.instance, &Solution::from_state(&state)).is_feasible();
+        debug_assert!(feasible, "Placements of a leaf must be feasible");
+        if feasible && state.obj_value() < self.incumbent() {
+            self.best_feasible = Some((state.obj_value(), state));
+            self.improved_at = self.nodes;
+        }
+    }
+}
+
+// Free intervals of a resource within the horizon, between its tasks (by start time)
+fn gaps(instance: &Instance, tasks: &[usize]) -> Vec<(usize, usize)> {
+    let mut gaps = Vec::new();
+    let mut prev = 0;
+    for task_id in tasks.iter() {
+        let task = &instance.tasks()[*task_id];
+        gaps.push((prev, task.start().min(instance.horizon())));
+        prev = task.end();
+    }
+    if prev < instance.horizon() {
+        gaps.push((prev, instance.horizon()));
+    }
+
+    gaps.into_iter().filter(|(start, end)| start < end).collect()
+}
+
+// Latest end in [earliest, latest] of a job of the given length that fits into a gap
+fn latest_end(gaps: &[(usize, usize)], length: usize, earliest: usize, latest: usize) -> Option<usize> {
+    for (start, end) in gaps.iter().rev() {
+        let candidate = latest.min(*end);
+        if candidate < earliest { return None; }
+        if candidate >= start + length {
+            return Some(candidate);
+        }
+    }
+
+    None
+}
+
+// Fewest regular maintenances that cover the tasks of a resource (maintenances ending as late as possible), the major
+// maintenance may cover tasks as well. None if the tasks cannot be covered or the major maintenance does not fit.
+fn min_cover(instance: &Instance, tasks: &[usize]) -> Option<Cover> {
+    let gaps = gaps(instance, tasks);
+    let major_fits = gaps.iter().any(|(start, end)| end - start >= instance.duration_major());
+    let mut covers = vec![Cover { ends: Vec::new(), major: None }];
+    for task_id in tasks.iter() {
+        let task = &instance.tasks()[*task_id];
+        if task.end() <= instance.time_regular() { continue; }
+        let earliest = task.end() - instance.time_regular();
+        let mut next = Vec::new();
+        for cover in covers.into_iter() {
+            let last = cover.ends.iter().chain(cover.major.iter()).max().copied();
+            if last.is_some_and(|last| last >= earliest) {
+                next.push(cover);
+                continue;
+            }
+            if cover.major.is_none() {
+                if let Some(end) = latest_end(&gaps, instance.duration_major(), earliest, task.start()) {
+                    next.push(Cover { ends: cover.ends.clone(), major: Some(end) });
+                }
+            }
+            if let Some(end) = latest_end(&gaps, instance.duration_regular(), earliest, task.start()) {
+                let mut ends = cover.ends;
+                ends.push(end);
+                next.push(Cover { ends, major: cover.major });
+            }
+        }
+        covers = pareto(next, major_fits);
+        if covers.is_empty() { return None; }
+    }
+
+    // Without a covering major maintenance it needs a gap of its own
+    covers.into_iter()
+        .filter(|cover| cover.major.is_some() || major_fits)
+        .min_by_key(|cover| (cover.ends.len(), cover.major.is_none()))
+}
+
+// Covers not dominated by another one with at most as many regular maintenances and a later last end. A cover that
+// still has the major maintenance left is at least as good as one that used it, if it fits into a gap of its own.
+fn pareto(covers: Vec<Cover>, major_fits: bool) -> Vec<Cover> {
+    let last = |cover: &Cover| cover.ends.iter().chain(cover.major.iter()).max().copied();
+    let dominates = |a: &Cover, b: &Cover| {
+        let major = if major_fits { a.major.is_none() || b.major.is_some() } else { a.major.is_some() == b.major.is_some() };
+        a.ends.len() <= b.ends.len() && last(a) >= last(b) && major
+    };
+    let mut kept: Vec<Cover> = Vec::new();
+    for cover in covers.into_iter() {
+        if kept.iter().any(|other| dominates(other, &cover)) { continue; }
+        kept.retain(|other| !dominates(&cover, other));
+        kept.push(cover);
+    }
+
+    kept
+}
+
+// Maintenance layouts of a resource with at most `max_regular` regular maintenances: the gaps between its tasks holding
+// the major and the regular maintenances, with the ranges of their ends that keep the tasks covered. A gap holds at
+// most one regular maintenance, after the major maintenance if they share it, and every regular maintenance covers a
+// task (others could be left out).
+fn layouts(instance: &Instance, res: usize, tasks: &[usize], max_regular: usize) -> Vec<Vec<Placement>> {
+    let mut layouts = Vec::new();
+    extend_layout(instance, res, tasks, max_regular, 0, Vec::new(), true, &mut layouts);
+    layouts.sort_by_key(|layout| layout.len());
+
+    layouts
+}
+
+// Fills the gap before the task at `gap` (after the last task if `gap` is the number of tasks), `covering` is whether
+// the last regular maintenance covers a task so far
+#[allow(clippy::too_many_arguments)]
+fn extend_layout(instance: &Instance, res: usize, tasks: &[usize], max_regular: usize, gap: usize, layout: Vec<Placement>, covering: bool, layouts: &mut Vec<Vec<Placement>>) {
+    let start = if gap == 0 { 0 } else { instance.tasks()[tasks[gap - 1]].end() };
+    let end = tasks.get(gap).map_or(instance.horizon(), |task_id| instance.tasks()[*task_id].start().min(instance.horizon()));
+    let width = end.saturating_sub(start);
+    let (major, regular) = (instance.duration_major(), instance.duration_regular());
+    let has_major = layout.iter().any(|placement| placement.major);
+    let can_add_regular = layout.iter().filter(|placement| !placement.major).count() < max_regular;
+    let placement = |major: bool, length: usize, earliest: usize, latest: usize, follows: bool| Placement { res, major, length, earliest, latest, follows };
+
+    let mut options = vec![(layout.clone(), covering)];
+    if covering {   // A later maintenance would leave the last regular one without a task
+        if width >= regular && can_add_regular {
+            options.push(([layout.clone(), vec![placement(false, regular, start + regular, end, false)]].concat(), false));
+        }
+        if !has_major && width >= major {
+            options.push(([layout.clone(), vec![placement(true, major, start + major, end, false)]].concat(), true));
+        }
+        if !has_major && width >= major + regular && can_add_regular {
+            let both = vec![placement(true, major, start + major, end - regular, false), placement(false, regular, start + major + regular, end, true)];
+            options.push(([layout, both].concat(), false));
+        }
+    }
+
+    for (mut layout, mut covering) in options.into_iter() {
+        let Some(task_id) = tasks.get(gap) else {
+            if covering && layout.iter().any(|placement| placement.major) {
+                layouts.push(layout);
+            }
+            continue;
+        };
+        let task = &instance.tasks()[*task_id];
+        if task.end() > instance.time_regular() {
+            // The last maintenance before the task must end within its coverage window
+            let Some(last) = layout.last_mut() else { continue; };
+            last.earliest = last.earliest.max(task.end() - instance.time_regular());
+            if last.earliest > last.latest { continue; }
+            covering = true;
+        }
+        extend_layout(instance, res, tasks, max_regular, gap + 1, layout, covering, layouts);
+    }
+}
+
+// Keeps a regular maintenance after the major maintenance it shares a gap with, false if a range becomes empty
+fn propagate(placements: &mut [Placement]) -> bool {
+    for i in 1..placements.len() {
+        if placements[i].follows {
+            placements[i].earliest = placements[i].earliest.max(placements[i - 1].earliest + placements[i].length);
+            placements[i - 1].latest = placements[i - 1].latest.min(placements[i].latest - placements[i].length);
+        }
+    }
+
+    placements.iter().all(|placement| placement.earliest <= placement.latest)
+}
+
+// Lower bound of the objective over the ranges of the placements: the maintenance time plus twice the smallest overlap
+// of each pair of maintenances on different resources. Also returns the widest range of a pair whose overlap is not
+// fixed yet. None if two major maintenances overlap.
+fn overlap_bound(placements: &[Placement]) -> Option<(usize, Option<usize>)> {
+    let mut bound = placements.iter().map(|placement| placement.length).sum::<usize>();
+    let mut branch: Option<usize> = None;
+    for (i, a) in placements.iter().enumerate() {
+        for (j, b) in placements.iter().enumerate().skip(i + 1).filter(|(_, b)| b.res != a.res) {
+            let (min, max) = overlap_range(a, b);
+            if a.major && b.major && min > 0 { return None; }
+            bound += 2 * min;
+            if max > min {
+                let wider = if a.width() >= b.width() { i } else { j };
+                if branch.is_none_or(|k| placements[wider].width() > placements[k].width()) {
+                    branch = Some(wider);
+                }
+            }
+        }
+    }
+
+    Some((bound, branch))
+}
+
+// Smallest and largest overlap of two maintenances over their ranges. As a function of the difference of the ends the
+// overlap is zero, rises, stays flat and falls again, the smallest is at a border of the range of the difference and the
+// largest at a border or a kink.
+fn overlap_range(a: &Placement, b: &Placement) -> (usize, usize) {
+    let (length_a, length_b) = (a.length as i64, b.length as i64);
+    let overlap = |diff: i64| (diff.min(0) - (diff - length_a).max(-length_b)).max(0) as usize;
+    let lowest = a.earliest as i64 - b.latest as i64;
+    let highest = a.latest as i64 - b.earliest as i64;
+    let min = overlap(lowest).min(overlap(highest));
+    let max = [lowest, highest, 0, length_a - length_b].into_iter()
+        .map(|diff| overlap(diff.clamp(lowest, highest)))
+        .max()
+        .unwrap();
+
+    (min, max)
+}
+
+impl Placement {
+    fn width(&self) -> usize {
+        self.latest - self.earliest
+    }
+}
+
+impl Default for BBParameters {
+    fn default() -> Self {
+        BBParameters { seed_iterations: 1_000_000 }
+    }
+}
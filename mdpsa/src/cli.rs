@@ -0,0 +1,572 @@
+use std::{fs, path::PathBuf, process, time::Duration};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use mdpsa::{acceptance::Acceptance, adaptive_weights::AdaptionParameters, alns::ALNSParameters, branch_and_bound::BBParameters, iterated_local_search::ILSParameters, memetic::GAParameters, parallel_tempering::PTParameters, tabu_search::TSParameters, Algorithm, cooling::{Cooling, Reheat}, neighborhood::NeighborhoodConfig, restart::{Restart, RestartStrategy, RestartTrigger}, simulated_annealing::Calibration, SAParameters};
+
+
+#[derive(Parser, Debug)]
+#[command(name = "mdpsa", about = "Simulated Annealing solver for the Maintenance Distribution Problem")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Solve the given instances and report every run
+    Solve(SolveArgs),
+    /// Benchmark the given instances and report aggregated results per instance
+    Bench(BenchArgs),
+    /// Check a solution file against an instance and recompute its objective
+    Validate(ValidateArgs),
+    /// Print the default operator configuration (as template for --operators)
+    Operators,
+    /// Write a time-indexed MILP model of an instance for external solvers
+    ExportMilp(ExportMilpArgs)
+}
+
+#[derive(Args, Debug)]
+pub struct SolveArgs {
+    /// Instance files, directories or glob patterns
+    #[arg(required = true)]
+    pub instances: Vec<String>,
+
+    /// Number of independent runs per instance
+    #[arg(short, long, default_value_t = 1, value_parser = at_least_one)]
+    pub runs: usize,
+
+    #[command(flatten)]
+    pub run: RunArgs
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Instance files, directories or glob patterns
+    #[arg(default_value = "./instances")]
+    pub instances: Vec<String>,
+
+    /// Number of independent runs per instance
+    #[arg(short, long, default_value_t = 10, value_parser = at_least_one)]
+    pub runs: usize,
+
+    #[command(flatten)]
+    pub run: RunArgs
+}
+
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// Instance file
+    pub instance: PathBuf,
+
+    /// Solution file
+    pub solution: PathBuf
+}
+
+#[derive(Args, Debug)]
+pub struct ExportMilpArgs {
+    /// Instance file
+    pub instance: PathBuf,
+
+    /// Model file (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// File format of the model
+    #[arg(long, value_enum, default_value_t = MilpFormat::Lp)]
+    pub format: MilpFormat,
+
+    /// Length of a period (default: gcd of all times of the instance, the model is exact), must divide both
+    /// maintenance durations, a coarser step gives an upper bound
+    #[arg(long)]
+    pub step: Option<usize>
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Time limit per run in seconds
+    #[arg(short, long, default_value_t = 600)]
+    pub time_limit: u64,
+
+    /// Search algorithm of each run
+    #[arg(long, value_enum, default_value_t = AlgorithmArg::Annealing)]
+    pub algorithm: AlgorithmArg,
+
+    /// Number of replicas (threads) of parallel tempering
+    #[arg(long, default_value_t = PTParameters::default().replicas)]
+    pub replicas: usize,
+
+    /// Iterations of each replica between two exchanges of parallel tempering
+    #[arg(long, default_value_t = PTParameters::default().exchange_interval)]
+    pub exchange_interval: usize,
+
+    /// Runs of an instance share their best solution, restarts continue from the best of all runs
+    #[arg(long, requires = "restart_trigger")]
+    pub cooperative: bool,
+
+    /// Candidate moves sampled per iteration of tabu search
+    #[arg(long, default_value_t = TSParameters::default().candidates)]
+    pub candidates: usize,
+
+    /// Iterations a removed assignment or maintenance position stays tabu
+    #[arg(long, default_value_t = TSParameters::default().tenure)]
+    pub tenure: usize,
+
+    /// Smallest fraction of the tasks (or of the horizon) an ALNS destroy operator removes
+    #[arg(long, default_value_t = ALNSParameters::default().min_removal)]
+    pub min_removal: f64,
+
+    /// Largest fraction of the tasks (or of the horizon) an ALNS destroy operator removes
+    #[arg(long, default_value_t = ALNSParameters::default().max_removal)]
+    pub max_removal: f64,
+
+    /// k of the ALNS regret-k repair
+    #[arg(long, default_value_t = ALNSParameters::default().regret)]
+    pub regret: usize,
+
+    /// Individuals of the memetic algorithm
+    #[arg(long, default_value_t = GAParameters::default().population)]
+    pub population: usize,
+
+    /// Random operator moves applied to each child of the memetic algorithm
+    #[arg(long, default_value_t = GAParameters::default().mutation_moves)]
+    pub mutation_moves: usize,
+
+    /// Iterations of the annealing run that improves each individual of the memetic algorithm
+    #[arg(long, default_value_t = GAParameters::default().local_iterations)]
+    pub local_iterations: usize,
+
+    /// Moves sampled per descent step of iterated local search
+    #[arg(long, default_value_t = ILSParameters::default().samples, value_parser = at_least_one)]
+    pub descent_samples: usize,
+
+    /// Random operator moves per kick of iterated local search
+    #[arg(long, default_value_t = ILSParameters::default().kick_moves, value_parser = at_least_one)]
+    pub kick_moves: usize,
+
+    /// Relative worsening of the local optimum after a kick that iterated local search still accepts
+    #[arg(long, default_value_t = ILSParameters::default().tolerance)]
+    pub tolerance: f64,
+
+    /// Iterations of the annealing run that seeds the incumbent of branch and bound (0 = none), capped by -i and -t
+    #[arg(long, default_value_t = BBParameters::default().seed_iterations)]
+    pub seed_iterations: usize,
+
+    /// Operator pool and selection weights as JSON file (see the operators command)
+    #[arg(long)]
+    pub operators: Option<PathBuf>,
+
+    /// Adapt the operator weights to the scores the operators earn during the run
+    #[arg(long)]
+    pub adaptive: bool,
+
+    /// Run a fixed number of iterations instead of cooling by the time limit
+    #[arg(short, long)]
+    pub iterations: Option<usize>,
+
+    /// Seed of the first run (run i uses seed + i), random if not given
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Number of runs executed in parallel (default: all runs at once)
+    #[arg(short = 'j', long)]
+    pub threads: Option<usize>,
+
+    /// Write the results to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Write a JSON report of every run (including operator weights and statistics) to this file
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// Print a table of per-operator statistics (calls, no-ops, accepts, ...) per instance to stderr
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Directory for the best solution of each instance (default: next to the output file)
+    #[arg(long)]
+    pub solutions: Option<PathBuf>,
+
+    /// Start from a solution file, or from <DIR>/<instance>.sol.json if a directory is given
+    #[arg(short, long)]
+    pub warm_start: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub sa: SAArgs
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum AlgorithmArg {
+    /// Simulated annealing
+    Annealing,
+    /// Parallel tempering: replicas on a temperature ladder from the final to the initial temperature exchange states
+    Tempering,
+    /// Tabu search: best of --candidates sampled moves per iteration, reverting recent moves is tabu
+    Tabu,
+    /// Adaptive large neighborhood search: destroy and repair operators with adaptive weights and annealing acceptance
+    Alns,
+    /// Memetic algorithm: a population of states bred by crossover and mutation, children improved by short annealing runs
+    Memetic,
+    /// Iterated local search: sampled best-improvement descents alternate with random kicks
+    Ils,
+    /// Exact branch and bound for small instances, reports a lower bound (-i limits the nodes)
+    Exact
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum MilpFormat {
+    /// CPLEX LP
+    Lp,
+    /// Free MPS
+    Mps
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum AcceptanceArg {
+    /// Accept worsening moves with probability e^(-delta/T)
+    Metropolis,
+    /// Accept if not worse than the objective --history-length iterations ago
+    LateAcceptance,
+    /// Accept worsening moves with a delta below the temperature
+    Threshold,
+    /// Accept below a level sinking from the initial to the best objective over the run
+    GreatDeluge,
+    /// Accept within --deviation of the best objective
+    RecordToRecord
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CoolingArg {
+    Geometric,
+    Linear,
+    LundyMees,
+    Logarithmic,
+    Exponential
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum RestartArg {
+    /// Best feasible state so far
+    Best,
+    /// Best feasible state after --restart-moves random moves
+    Perturbed,
+    /// New random initial state
+    Fresh
+}
+
+#[derive(Args, Debug)]
+pub struct SAArgs {
+    /// Initial temperature of the annealing schedule
+    #[arg(long, default_value_t = SAParameters::default().initial_temperature())]
+    pub initial_temperature: f64,
+
+    /// Final temperature of the annealing schedule
+    #[arg(long, default_value_t = SAParameters::default().final_temperature())]
+    pub final_temperature: f64,
+
+    /// Derive the initial and final temperature from sampled moves of the initial state (overrides the temperatures)
+    #[arg(long)]
+    pub calibrate: bool,
+
+    /// Number of sampled moves for the calibration
+    #[arg(long, default_value_t = Calibration::default().samples)]
+    pub calibration_samples: usize,
+
+    /// Target mean acceptance probability of worsening moves at the start
+    #[arg(long, default_value_t = Calibration::default().initial_acceptance, value_parser = probability)]
+    pub initial_acceptance: f64,
+
+    /// Target mean acceptance probability of worsening moves at the end
+    #[arg(long, default_value_t = Calibration::default().final_acceptance, value_parser = probability)]
+    pub final_acceptance: f64,
+
+    /// Acceptance criterion of worsening moves
+    #[arg(long, value_enum, default_value_t = AcceptanceArg::Metropolis)]
+    pub acceptance: AcceptanceArg,
+
+    /// History length of late acceptance hill climbing
+    #[arg(long, default_value_t = 1000, value_parser = at_least_one)]
+    pub history_length: usize,
+
+    /// Allowed relative deviation from the best objective of record-to-record travel
+    #[arg(long, default_value_t = 0.01, value_parser = non_negative)]
+    pub deviation: f64,
+
+    /// Cooling schedule from the initial to the final temperature
+    #[arg(long, value_enum, default_value_t = CoolingArg::Geometric)]
+    pub cooling: CoolingArg,
+
+    /// Time constant in seconds of the exponential (time-based) cooling, with -i the iterations are spread over -t
+    #[arg(long, default_value_t = 60.0, value_parser = positive)]
+    pub tau: f64,
+
+    /// Reheat after this many iterations without improvement
+    #[arg(long)]
+    pub reheat_after: Option<usize>,
+
+    /// Fraction of the cooling progress undone by a reheat
+    #[arg(long, default_value_t = 0.5, value_parser = fraction)]
+    pub reheat_fraction: f64,
+
+    /// Where the search continues from after a restart
+    #[arg(long, value_enum, default_value_t = RestartArg::Best)]
+    pub restart: RestartArg,
+
+    /// Number of random moves applied to the best state by --restart perturbed
+    #[arg(long, default_value_t = 10)]
+    pub restart_moves: usize,
+
+    /// Restart after this many iterations without improvement
+    #[arg(long, group = "restart_trigger", value_parser = at_least_one)]
+    pub restart_after: Option<usize>,
+
+    /// Restart every n iterations
+    #[arg(long, group = "restart_trigger", value_parser = at_least_one)]
+    pub restart_every: Option<usize>,
+
+    /// Adapt the penalty weights every n iterations (raised while infeasible, lowered while feasible)
+    #[arg(long, value_parser = at_least_one)]
+    pub penalty_interval: Option<usize>,
+
+    /// Maximum weight of a penalty type with adaptive penalties
+    #[arg(long, default_value_t = SAParameters::default().max_penalty(), value_parser = at_least_one)]
+    pub max_penalty: usize,
+
+    /// Verify the incremental state against a full recomputation every n iterations (0 = never)
+    #[arg(long, default_value_t = SAParameters::default().verify_every())]
+    pub verify_every: usize
+}
+
+impl RunArgs {
+    /// Exits on combinations of arguments that cannot be used together
+    pub fn validate(&self) {
+        if self.cooperative && !matches!(self.algorithm, AlgorithmArg::Annealing) {
+            exit_with_error("--cooperative is only supported by --algorithm annealing");
+        }
+        // ALNS takes only the temperatures, cooling and acceptance from the annealing
+        if matches!(self.algorithm, AlgorithmArg::Alns) {
+            let unsupported = [
+                (self.sa.reheat_after.is_some(), "--reheat-after"),
+                (self.sa.restart_after.is_some(), "--restart-after"),
+                (self.sa.restart_every.is_some(), "--restart-every"),
+                (self.sa.penalty_interval.is_some(), "--penalty-interval")
+            ];
+            if let Some((_, option)) = unsupported.iter().find(|(set, _)| *set) {
+                exit_with_error(&format!("{} is not supported by --algorithm alns", option));
+            }
+        }
+        if let Err(err) = SAParameters::new(self.sa.initial_temperature, self.sa.final_temperature, self.sa.max_penalty) {
+            exit_with_error(&err.to_string());
+        }
+        if self.sa.calibrate && self.sa.initial_acceptance <= self.sa.final_acceptance {
+            exit_with_error("--initial-acceptance must be higher than --final-acceptance");
+        }
+    }
+
+    pub fn time_limit(&self) -> Duration {
+        Duration::from_secs(self.time_limit)
+    }
+
+    pub fn threads(&self, runs: usize) -> usize {
+        self.threads.unwrap_or(runs).clamp(1, runs.max(1))
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        match self.algorithm {
+            AlgorithmArg::Annealing => Algorithm::SimulatedAnnealing,
+            AlgorithmArg::Tempering => Algorithm::ParallelTempering(PTParameters {
+                replicas: self.replicas.max(1),
+                exchange_interval: self.exchange_interval.max(1)
+            }),
+            AlgorithmArg::Tabu => Algorithm::TabuSearch(TSParameters {
+                candidates: self.candidates.max(1),
+                tenure: self.tenure
+            }),
+            AlgorithmArg::Alns => Algorithm::AdaptiveLargeNeighborhoodSearch(ALNSParameters {
+                min_removal: self.min_removal.clamp(0.0, 1.0),
+                max_removal: self.max_removal.clamp(0.0, 1.0),
+                regret: self.regret.max(1),
+                ..ALNSParameters::default()
+            }),
+            AlgorithmArg::Memetic => Algorithm::Memetic(GAParameters {
+                population: self.population.max(2),
+                mutation_moves: self.mutation_moves,
+                local_iterations: self.local_iterations.max(1),
+                ..GAParameters::default()
+            }),
+            AlgorithmArg::Ils => Algorithm::IteratedLocalSearch(ILSParameters {
+                samples: self.descent_samples,
+                kick_moves: self.kick_moves,
+                tolerance: self.tolerance.max(0.0)
+            }),
+            AlgorithmArg::Exact => Algorithm::BranchAndBound(BBParameters {
+                seed_iterations: self.seed_iterations
+            })
+        }
+    }
+
+    pub fn neighborhood_config(&self) -> NeighborhoodConfig {
+        let config = match &self.operators {
+            Some(path) => NeighborhoodConfig::new_from_file(path)
+                .unwrap_or_else(|err| exit_with_error(&format!("cannot read operators {}: {}", path.display(), err))),
+            None => NeighborhoodConfig::default()
+        };
+        // Adaption parameters given in the operators file take precedence
+        if self.adaptive && config.adaptive().is_none() {
+            return config.with_adaptive(AdaptionParameters::default()).unwrap();    // Default parameters are valid
+        }
+
+        config
+    }
+
+    pub fn seed(&self, run: usize) -> u64 {
+        match self.seed {
+            Some(seed) => seed.wrapping_add(run as u64),
+            None => rand::random()
+        }
+    }
+
+    pub fn solution_dir(&self) -> Option<PathBuf> {
+        match (&self.solutions, &self.output) {
+            (Some(dir), _) => Some(dir.clone()),
+            (None, Some(output)) => Some(output.parent().map(|p| p.to_path_buf()).unwrap_or_default()),
+            (None, None) => None
+        }
+    }
+
+    pub fn warm_start_file(&self, instance_name: &str) -> Option<PathBuf> {
+        let path = self.warm_start.as_ref()?;
+        if path.is_dir() {
+            return Some(path.join(format!("{}.sol.json", instance_name)));
+        }
+        Some(path.clone())
+    }
+}
+
+impl SAArgs {
+    pub fn to_parameters(&self) -> SAParameters {
+        let cooling = match self.cooling {
+            CoolingArg::Geometric => Cooling::Geometric,
+            CoolingArg::Linear => Cooling::Linear,
+            CoolingArg::LundyMees => Cooling::LundyMees,
+            CoolingArg::Logarithmic => Cooling::Logarithmic,
+            CoolingArg::Exponential => Cooling::Exponential { tau: self.tau }
+        };
+        let acceptance = match self.acceptance {
+            AcceptanceArg::Metropolis => Acceptance::Metropolis,
+            AcceptanceArg::LateAcceptance => Acceptance::LateAcceptance { length: self.history_length },
+            AcceptanceArg::Threshold => Acceptance::ThresholdAccepting,
+            AcceptanceArg::GreatDeluge => Acceptance::GreatDeluge,
+            AcceptanceArg::RecordToRecord => Acceptance::RecordToRecord { deviation: self.deviation }
+        };
+        let mut parameters = SAParameters::new(self.initial_temperature, self.final_temperature, self.max_penalty)
+            .expect("Temperatures are checked by RunArgs::validate")
+            .with_cooling(cooling)
+            .with_acceptance(acceptance)
+            .with_verify_every(self.verify_every);
+        let trigger = match (self.restart_after, self.restart_every) {
+            (Some(after), _) => Some(RestartTrigger::Stagnation(after)),
+            (None, Some(interval)) => Some(RestartTrigger::Interval(interval)),
+            (None, None) => None
+        };
+        if let Some(trigger) = trigger {
+            let strategy = match self.restart {
+                RestartArg::Best => RestartStrategy::Best,
+                RestartArg::Perturbed => RestartStrategy::PerturbedBest { moves: self.restart_moves },
+                RestartArg::Fresh => RestartStrategy::Fresh
+            };
+            parameters = parameters.with_restart(Restart { strategy, trigger });
+        }
+        if let Some(interval) = self.penalty_interval {
+            parameters = parameters.with_adaptive_penalty(interval);
+        }
+        if self.calibrate {
+            parameters = parameters.with_calibration(Calibration {
+                samples: self.calibration_samples,
+                initial_acceptance: self.initial_acceptance,
+                final_acceptance: self.final_acceptance
+            });
+        }
+        match self.reheat_after {
+            Some(after) => parameters.with_reheat(Reheat { after, fraction: self.reheat_fraction }),
+            None => parameters
+        }
+    }
+}
+
+// Expands files, directories (all contained *.json files) and glob patterns into a sorted list of instance paths
+pub fn expand_instances(patterns: &[String]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for pattern in patterns.iter() {
+        let path = PathBuf::from(pattern);
+        if path.is_dir() {
+            let mut dir_paths = fs::read_dir(&path).unwrap_or_else(|err| exit_with_error(&format!("cannot read directory {}: {}", pattern, err)))
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect::<Vec<PathBuf>>();
+            dir_paths.sort();
+            paths.append(&mut dir_paths);
+            continue;
+        }
+        let matches = glob::glob(pattern).unwrap_or_else(|err| exit_with_error(&format!("invalid pattern {}: {}", pattern, err)))
+            .filter_map(|entry| entry.ok())
+            .collect::<Vec<PathBuf>>();
+        if matches.is_empty() {
+            exit_with_error(&format!("no instance found for {}", pattern));
+        }
+        paths.extend(matches);
+    }
+
+    paths
+}
+
+// Parses a count that has to be positive
+fn at_least_one(arg: &str) -> Result<usize, String> {
+    match arg.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(value) => Ok(value),
+        Err(err) => Err(err.to_string())
+    }
+}
+
+// Parses a number that has to be positive
+fn positive(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+        Ok(_) => Err("must be positive".to_string()),
+        Err(err) => Err(err.to_string())
+    }
+}
+
+// Parses a number that must not be negative
+fn non_negative(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(value) if value >= 0.0 && value.is_finite() => Ok(value),
+        Ok(_) => Err("must not be negative".to_string()),
+        Err(err) => Err(err.to_string())
+    }
+}
+
+// Parses a fraction in [0, 1]
+fn fraction(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(value),
+        Ok(_) => Err("must be in [0, 1]".to_string()),
+        Err(err) => Err(err.to_string())
+    }
+}
+
+// Parses a probability strictly between 0 and 1
+fn probability(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(value) if value > 0.0 && value < 1.0 => Ok(value),
+        Ok(_) => Err("must be in (0, 1)".to_string()),
+        Err(err) => Err(err.to_string())
+    }
+}
+
+pub fn exit_with_error(msg: &str) -> ! {
+    eprintln!("error: {}", msg);
+    process::exit(2)
+}
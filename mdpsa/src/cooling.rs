@@ -0,0 +1,86 @@
+use std::{f64::consts::E, fmt, time::Duration};
+
+
+/// Temperature of the annealing over the progress of the run
+pub trait CoolingSchedule: fmt::Debug + Send {
+    /// `progress` is the used fraction of the budget in [0, 1], `elapsed` the time since the schedule started
+    fn temperature(&self, initial: f64, final_temperature: f64, progress: f64, elapsed: Duration) -> f64;
+}
+
+/// Available cooling schedules, all go from the initial to the final temperature
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cooling {
+    Geometric,
+    Linear,
+    LundyMees,
+    Logarithmic,
+    Exponential { tau: f64 }    // By time with time constant tau (seconds), not bound to the budget
+}
+
+/// Go back to a higher temperature if the search stagnates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reheat {
+    pub after: usize,     // Iterations without improvement (and since the last reheat)
+    pub fraction: f64     // Fraction of the cooling progress that is undone
+}
+
+#[derive(Debug)]
+struct Geometric;
+
+#[derive(Debug)]
+struct Linear;
+
+#[derive(Debug)]
+struct LundyMees;
+
+#[derive(Debug)]
+struct Logarithmic;
+
+#[derive(Debug)]
+struct Exponential {
+    tau: f64
+}
+
+impl Cooling {
+    pub fn build(&self) -> Box<dyn CoolingSchedule> {
+        match *self {
+            Cooling::Geometric => Box::new(Geometric),
+            Cooling::Linear => Box::new(Linear),
+            Cooling::LundyMees => Box::new(LundyMees),
+            Cooling::Logarithmic => Box::new(Logarithmic),
+            Cooling::Exponential { tau } => Box::new(Exponential { tau })
+        }
+    }
+}
+
+impl CoolingSchedule for Geometric {
+    fn temperature(&self, initial: f64, final_temperature: f64, progress: f64, _: Duration) -> f64 {
+        initial * (final_temperature / initial).powf(progress)
+    }
+}
+
+impl CoolingSchedule for Linear {
+    fn temperature(&self, initial: f64, final_temperature: f64, progress: f64, _: Duration) -> f64 {
+        initial + (final_temperature - initial) * progress
+    }
+}
+
+// T_k+1 = T_k / (1 + beta * T_k) in closed form, beta chosen to end at the final temperature
+impl CoolingSchedule for LundyMees {
+    fn temperature(&self, initial: f64, final_temperature: f64, progress: f64, _: Duration) -> f64 {
+        initial / (1.0 + progress * (initial / final_temperature - 1.0))
+    }
+}
+
+// T0 / (1 + c * ln(1 + k)), scaled such that the log term is 1 at the end
+impl CoolingSchedule for Logarithmic {
+    fn temperature(&self, initial: f64, final_temperature: f64, progress: f64, _: Duration) -> f64 {
+        initial / (1.0 + (initial / final_temperature - 1.0) * (1.0 + (E - 1.0) * progress).ln())
+    }
+}
+
+impl CoolingSchedule for Exponential {
+    fn temperature(&self, initial: f64, final_temperature: f64, _: f64, elapsed: Duration) -> f64 {
+        (initial * (-elapsed.as_secs_f64() / self.tau).exp()).max(final_temperature)
+    }
+}
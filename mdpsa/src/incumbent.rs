@@ -0,0 +1,38 @@
+use std::sync::Mutex;
+
+use super::state::State;
+
+
+/// Best feasible state shared by cooperating runs: runs publish their new bests and adopt it when they stagnate
+#[derive(Debug, Default)]
+pub struct Incumbent {
+    best: Mutex<Option<(usize, State)>>
+}
+
+impl Incumbent {
+    pub fn new() -> Self {
+        Incumbent::default()
+    }
+
+    /// Replaces the incumbent if the state is better, returns whether it was replaced
+    pub fn offer(&self, obj: usize, state: &State) -> bool {
+        let mut best = self.best.lock().unwrap();
+        let better = match *best {
+            Some((prev_best, _)) => obj < prev_best,
+            None => true
+        };
+        if better {
+            *best = Some((obj, state.clone()));
+        }
+
+        better
+    }
+
+    pub fn get(&self) -> Option<(usize, State)> {
+        self.best.lock().unwrap().clone()
+    }
+
+    pub fn obj_value(&self) -> Option<usize> {
+        self.best.lock().unwrap().as_ref().map(|(obj, _)| *obj)
+    }
+}
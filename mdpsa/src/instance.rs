@@ -1,8 +1,9 @@
 use serde_json::from_str;
 use serde::Deserialize;
-use std::fs::read_to_string;
+use std::{cmp, error, fmt, fs::read_to_string, io, path::Path};
 
 
+/// Problem instance as read from the instance JSON files
 #[derive(Deserialize, Debug, Clone)]
 pub struct Instance {
     resources: usize,
@@ -19,9 +20,67 @@ pub struct Task {
     length: usize
 }
 
+/// Reason why an instance could not be loaded
+#[derive(Debug)]
+pub enum InstanceError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Semantic(Vec<SemanticError>)
+}
+
+/// Properties of an instance that make it unsolvable, or (see `is_fatal`) that the solver tolerates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemanticError {
+    NoResources,
+    MajMaintExceedsHorizon { duration_major: usize, horizon: usize },
+    TimeRegularTooShort { time_regular: usize, duration_regular: usize },
+    TaskOutsideHorizon { task_id: usize, start: usize, end: usize, horizon: usize },
+    TaskNeverCovered { task_id: usize }
+}
+
 impl Instance {
-    pub fn new_from_file(path: &str) -> Self {
-        from_str(&read_to_string(path).unwrap()).unwrap()
+    pub fn new_from_file(path: &Path) -> Result<Self, InstanceError> {
+        let instance: Instance = from_str(&read_to_string(path)?)?;
+        let errors = instance.check().into_iter().filter(|err| err.is_fatal()).collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(InstanceError::Semantic(errors));
+        }
+
+        Ok(instance)
+    }
+
+    // Checks for properties that make the instance unsolvable, and for tasks ending after the horizon (not fatal, the
+    // benchmark instances have them, maintenances must end within the horizon though)
+    pub fn check(&self) -> Vec<SemanticError> {
+        let mut errors = Vec::new();
+        if self.resources == 0 {
+            errors.push(SemanticError::NoResources);
+        }
+        if self.duration_major > self.horizon {
+            errors.push(SemanticError::MajMaintExceedsHorizon { duration_major: self.duration_major, horizon: self.horizon });
+        }
+        if self.time_regular < self.duration_regular {
+            errors.push(SemanticError::TimeRegularTooShort { time_regular: self.time_regular, duration_regular: self.duration_regular });
+        }
+        for (task_id, task) in self.tasks.iter().enumerate() {
+            if task.end() > self.horizon {
+                errors.push(SemanticError::TaskOutsideHorizon { task_id, start: task.start(), end: task.end(), horizon: self.horizon });
+                if task.start() >= self.horizon { continue; }
+            }
+            if task.end() <= self.time_regular { continue; }  // Covered by the initial timeframe
+            // A maintenance covering the task has to end in [task end - time_regular, task start]
+            let earliest_cover = cmp::max(task.end() - self.time_regular, self.duration_regular);
+            if task.length() > self.time_regular || earliest_cover > task.start() {
+                errors.push(SemanticError::TaskNeverCovered { task_id });
+            }
+        }
+
+        errors
+    }
+
+    /// Findings of `check` that do not keep the instance from being solved
+    pub fn warnings(&self) -> Vec<SemanticError> {
+        self.check().into_iter().filter(|err| !err.is_fatal()).collect()
     }
 
     pub fn resources(&self) -> usize {
@@ -61,4 +120,121 @@ impl Task {
     pub fn end(&self) -> usize {
         self.start + self.length
     }
-}
\ No newline at end of file
+}
+
+impl SemanticError {
+    /// Whether the instance cannot be solved, a task that only ends after the horizon is scheduled as usual
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            SemanticError::TaskOutsideHorizon { start, horizon, .. } => start >= horizon,
+            _ => true
+        }
+    }
+}
+
+impl fmt::Display for InstanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstanceError::Io(err) => write!(f, "cannot read instance: {}", err),
+            InstanceError::Parse(err) => write!(f, "cannot parse instance: {}", err),
+            InstanceError::Semantic(errors) => {
+                write!(f, "invalid instance:")?;
+                for err in errors.iter() {
+                    write!(f, "\n  {}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SemanticError::NoResources => write!(f, "no resources"),
+            SemanticError::MajMaintExceedsHorizon { duration_major, horizon } => write!(f, "major maintenance duration {} exceeds horizon {}", duration_major, horizon),
+            SemanticError::TimeRegularTooShort { time_regular, duration_regular } => write!(f, "time_regular {} is shorter than regular maintenance duration {}", time_regular, duration_regular),
+            SemanticError::TaskOutsideHorizon { task_id, start, end, horizon } => write!(f, "task {} runs from {} to {}, beyond the horizon {}", task_id, start, end, horizon),
+            SemanticError::TaskNeverCovered { task_id } => write!(f, "task {} can never be covered by a maintenance", task_id)
+        }
+    }
+}
+
+impl error::Error for InstanceError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            InstanceError::Io(err) => Some(err),
+            InstanceError::Parse(err) => Some(err),
+            InstanceError::Semantic(_) => None
+        }
+    }
+}
+
+impl From<io::Error> for InstanceError {
+    fn from(err: io::Error) -> Self {
+        InstanceError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for InstanceError {
+    fn from(err: serde_json::Error) -> Self {
+        InstanceError::Parse(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Horizon 100, maintenances of 5 and 10, coverage every 40 time units
+    fn instance(resources: usize, tasks: &[(usize, usize)]) -> Instance {
+        let tasks = tasks.iter().map(|(start, length)| Task { start: *start, length: *length }).collect();
+        Instance { resources, horizon: 100, duration_regular: 5, duration_major: 10, time_regular: 40, tasks }
+    }
+
+    #[test]
+    fn accepts_valid_instance() {
+        let instance = instance(2, &[(0, 30), (35, 30), (10, 20)]);
+        assert!(instance.check().is_empty());
+        assert!(instance.warnings().is_empty());
+    }
+
+    #[test]
+    fn reports_unsolvable_instances() {
+        assert_eq!(instance(0, &[]).check(), vec![SemanticError::NoResources]);
+
+        let mut short_horizon = instance(1, &[]);
+        short_horizon.horizon = 8;
+        assert_eq!(short_horizon.check(), vec![SemanticError::MajMaintExceedsHorizon { duration_major: 10, horizon: 8 }]);
+
+        let mut short_time_regular = instance(1, &[]);
+        short_time_regular.time_regular = 4;
+        assert_eq!(short_time_regular.check(), vec![SemanticError::TimeRegularTooShort { time_regular: 4, duration_regular: 5 }]);
+    }
+
+    #[test]
+    fn reports_tasks_never_covered() {
+        // Longer than time_regular, and ending too soon after its start for a maintenance to fit before it
+        let instance = instance(1, &[(0, 45), (10, 50)]);
+        assert_eq!(instance.check(), vec![SemanticError::TaskNeverCovered { task_id: 0 }, SemanticError::TaskNeverCovered { task_id: 1 }]);
+    }
+
+    #[test]
+    fn reports_tasks_beyond_horizon() {
+        let instance = instance(1, &[(70, 40), (100, 10)]);
+        let ending_after = SemanticError::TaskOutsideHorizon { task_id: 0, start: 70, end: 110, horizon: 100 };
+        let starting_after = SemanticError::TaskOutsideHorizon { task_id: 1, start: 100, end: 110, horizon: 100 };
+        assert_eq!(instance.check(), vec![ending_after.clone(), starting_after.clone()]);
+        assert_eq!(instance.warnings(), vec![ending_after]);
+    }
+
+    #[test]
+    fn only_tasks_ending_after_horizon_are_not_fatal() {
+        assert!(!SemanticError::TaskOutsideHorizon { task_id: 0, start: 99, end: 110, horizon: 100 }.is_fatal());
+        assert!(SemanticError::TaskOutsideHorizon { task_id: 0, start: 100, end: 110, horizon: 100 }.is_fatal());
+        assert!(SemanticError::NoResources.is_fatal());
+        assert!(SemanticError::MajMaintExceedsHorizon { duration_major: 10, horizon: 8 }.is_fatal());
+        assert!(SemanticError::TimeRegularTooShort { time_regular: 4, duration_regular: 5 }.is_fatal());
+        assert!(SemanticError::TaskNeverCovered { task_id: 0 }.is_fatal());
+    }
+}
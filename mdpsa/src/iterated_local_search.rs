@@ -0,0 +1,165 @@
+use rand::rngs::StdRng;
+use std::mem;
+
+use super::{adaptive_weights::MoveOutcome, neighborhood::{ChangeToken, Neighborhood}, simulated_annealing::Termination, state::State};
+
+
+/// Parameters of the iterated local search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ILSParameters {
+    pub samples: usize,     // Moves sampled per descent step, the descent stops if none of them improves
+    pub kick_moves: usize,  // Random operator moves of a kick
+    pub tolerance: f64      // Relative worsening of the local optimum that is still accepted
+}
+
+/// Iterated local search: descents over sampled operator moves alternate with random kicks, the local optimum after
+/// a kick is kept if it is better or at most `tolerance` worse, otherwise the kick and descent are undone
+pub struct IteratedLocalSearch {
+    parameters: ILSParameters,
+    neighborhood: Neighborhood,
+    iterations: usize,
+    iterations_since_improvement: usize,
+    kicks: usize,
+    accepted_kicks: usize,
+    best_obj: usize,    // Best working objective (including penalties)
+    best_feasible: Option<(usize, State)>
+}
+
+impl IteratedLocalSearch {
+    /// A kick makes at least one move and a descent samples at least one move, lower values are raised to 1
+    pub fn new(neighborhood: Neighborhood, mut parameters: ILSParameters) -> Self {
+        parameters.kick_moves = parameters.kick_moves.max(1);
+        parameters.samples = parameters.samples.max(1);
+        let best_feasible = neighborhood.state().initial_best();
+
+        IteratedLocalSearch {
+            parameters,
+            neighborhood,
+            iterations: 0,
+            iterations_since_improvement: 0,
+            kicks: 0,
+            accepted_kicks: 0,
+            best_obj: usize::MAX,
+            best_feasible
+        }
+    }
+
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighborhood
+    }
+
+    pub fn get_best(&self) -> &Option<(usize, State)> {
+        &self.best_feasible
+    }
+
+    /// Moves made by descents and kicks
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn iterations_since_improvement(&self) -> usize {
+        self.iterations_since_improvement
+    }
+
+    pub fn kicks(&self) -> usize {
+        self.kicks
+    }
+
+    /// Kicks whose local optimum was kept
+    pub fn accepted_kicks(&self) -> usize {
+        self.accepted_kicks
+    }
+
+    pub fn solve(&mut self, termination: Termination) -> usize {
+        self.descend(termination, &mut Vec::new());
+        let mut current = self.neighborhood.state().working_obj_val();
+        while !termination.is_reached(self.iterations) {
+            let mut changes = Vec::new();
+            let iterations = self.iterations;
+            self.kick(termination, &mut changes);
+            self.descend(termination, &mut changes);
+            if self.iterations == iterations { break; }     // No move was made, the next round would not either
+
+            let obj_val = self.neighborhood.state().working_obj_val();
+            if obj_val as f64 <= current as f64 * (1.0 + self.parameters.tolerance) {
+                current = obj_val;
+                self.accepted_kicks += 1;
+            } else {
+                self.neighborhood.undo(&changes);
+            }
+        }
+
+        self.iterations
+    }
+
+    // Random moves regardless of their delta
+    fn kick(&mut self, termination: Termination, changes: &mut Vec<ChangeToken>) {
+        self.kicks += 1;
+        for _ in 0..self.parameters.kick_moves {
+            if termination.is_reached(self.iterations) { break; }
+            self.neighborhood.get_next();
+            changes.extend_from_slice(self.neighborhood.last_changes());
+            self.make(MoveOutcome::Accepted);
+        }
+    }
+
+    // Makes the best of the sampled moves as long as it improves
+    fn descend(&mut self, termination: Termination, changes: &mut Vec<ChangeToken>) {
+        while !termination.is_reached(self.iterations) {
+            // Operator and generator state of the best move, every sampled move is rolled back right away
+            let mut best: Option<(usize, usize, StdRng, f64)> = None;
+            let mut sampled = Vec::with_capacity(self.parameters.samples);
+            for i in 0..self.parameters.samples {
+                let operator = self.neighborhood.select_operator();
+                let rng = self.neighborhood.rng().clone();
+                let delta = self.neighborhood.apply(operator);
+                sampled.push(operator);
+                if delta < 0.0 && best.as_ref().is_none_or(|(_, _, _, best_delta)| delta < *best_delta) {
+                    best = Some((i, operator, rng, delta));
+                }
+                self.neighborhood.discard();
+            }
+            // All sampled moves but the chosen one are rejected
+            let chosen = best.as_ref().map(|(i, _, _, _)| *i);
+            for (i, operator) in sampled.into_iter().enumerate() {
+                if Some(i) != chosen {
+                    self.neighborhood.record_rejected(operator);
+                }
+            }
+
+            let (_, operator, rng, _) = match best {
+                Some(best) => best,
+                None => return  // Local optimum of the sample
+            };
+            // Replay the chosen move, its call was counted when it was sampled. The generator continues after the last
+            // sample, the next step must not sample the same moves again.
+            let rng = mem::replace(self.neighborhood.rng(), rng);
+            self.neighborhood.replay(operator);
+            *self.neighborhood.rng() = rng;
+            changes.extend_from_slice(self.neighborhood.last_changes());
+            self.make(MoveOutcome::Improved);
+        }
+    }
+
+    // Keeps the last move and tracks the best states
+    fn make(&mut self, outcome: MoveOutcome) {
+        self.iterations += 1;
+        self.iterations_since_improvement += 1;
+        let obj_val = self.neighborhood.state().working_obj_val();
+        if obj_val < self.best_obj {
+            self.neighborhood.accept(MoveOutcome::NewBest);
+            self.best_obj = obj_val;
+            self.iterations_since_improvement = 0;
+        } else {
+            self.neighborhood.accept(outcome);
+        }
+
+        self.neighborhood.state().update_best(&mut self.best_feasible);
+    }
+}
+
+impl Default for ILSParameters {
+    fn default() -> Self {
+        ILSParameters { samples: 20, kick_moves: 5, tolerance: 0.01 }
+    }
+}
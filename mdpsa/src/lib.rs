@@ -0,0 +1,40 @@
+//! Simulated Annealing solver for the Maintenance Distribution Problem.
+//!
+//! ```no_run
+//! use std::{path::Path, time::Duration};
+//! use mdpsa::{solve, Config, Instance, SAParameters};
+//!
+//! let instance = Instance::new_from_file(Path::new("instances/mdp-3-7-5.json")).unwrap();
+//! let config = Config::new(SAParameters::default(), Duration::from_secs(10), 42);
+//! let report = solve(&instance, &config);
+//! if let Some(solution) = report.best() {
+//!     println!("objective: {}", solution.objective());
+//! }
+//! ```
+
+pub mod instance;
+pub mod state;
+pub mod simulated_annealing;
+pub mod cooling;
+pub mod acceptance;
+pub mod restart;
+pub mod parallel_tempering;
+pub mod incumbent;
+pub mod tabu_search;
+pub mod alns;
+pub mod memetic;
+pub mod iterated_local_search;
+pub mod branch_and_bound;
+pub mod neighborhood;
+pub mod solution;
+pub mod validator;
+pub mod milp;
+pub mod adaptive_weights;
+mod solver;
+
+pub use instance::Instance;
+pub use neighborhood::Neighborhood;
+pub use simulated_annealing::{ParameterError, SAParameters, SimulatedAnnealing, Termination};
+pub use solution::Solution;
+pub use solver::{solve, Algorithm, Config, SolveReport};
+pub use state::State;
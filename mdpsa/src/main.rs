@@ -1,127 +1,296 @@
-mod instance;
-mod state;
-mod simulated_annealing;
-mod neighborhood;
+mod cli;
 
-use std::{fs, time::Instant};
+use std::{fs::{self, File}, io::{self, BufWriter, Write}, path::Path, process, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}};
 
+use clap::Parser;
 use crossbeam_utils::thread;
+use serde_json::json;
 
 
-use instance::Instance;
-use neighborhood::Neighborhood;
-use simulated_annealing::{SAParameters, SimulatedAnnealing};
-use state::State;
+use cli::{BenchArgs, Cli, Command, ExportMilpArgs, MilpFormat, RunArgs, SolveArgs, ValidateArgs};
+use mdpsa::{incumbent::Incumbent, instance::InstanceError, milp, neighborhood::NeighborhoodConfig, validator, Config, Instance, Solution, SolveReport};
 
 
 fn main() {
-    let runs = 10;
-    let timeout = 10*60*1000;   // 10 minutes
-    println!("instance, min, avg, num_feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime");
-    for path in fs::read_dir("./instances").unwrap() {
-        // let instance = Instance::new_from_file("./instances/mdp-3-7-5.json");
-
-        let instance_name = path.unwrap().path().to_str().unwrap().to_string();
-        let instance = Instance::new_from_file(&instance_name);
-        let instance_name = instance_name.split("\\").last().unwrap().split(".").next().unwrap();
-        println!("{}", results_to_string(run_multithreaded(instance, runs, timeout), &instance_name));
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Solve(args) => solve(args),
+        Command::Bench(args) => bench(args),
+        Command::Validate(args) => validate(args),
+        Command::Operators => println!("{}", NeighborhoodConfig::default().to_json()),
+        Command::ExportMilp(args) => export_milp(args)
     }
 }
 
-fn run_multithreaded(instance: Instance, runs: usize, timeout: usize) -> Vec<Result> {
-    thread::scope(|s| {
-        let mut handles = Vec::new();
-        for _ in 0..runs {
-            let instance_clone = instance.clone();
-            handles.push(s.spawn(move |_| {
-                run_instance(instance_clone, timeout)
-            }));
+fn solve(args: SolveArgs) {
+    args.run.validate();
+    let neighborhood = args.run.neighborhood_config();
+    let mut out = open_output(&args.run);
+    writeln!(out, "instance, run, seed, obj_val, feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime").unwrap();
+    let mut report = Vec::new();
+    for path in cli::expand_instances(&args.instances) {
+        let instance_name = instance_name(&path);
+        let instance = match load_instance(&path) {
+            Ok(instance) => instance,
+            Err(err) => {
+                eprintln!("skipping {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        let warm_start = load_warm_start(&instance_name, &args.run);
+        let results = run_multithreaded(instance, warm_start, &neighborhood, args.runs, &args.run);
+        for (run, result) in results.iter().enumerate() {
+            writeln!(out, "{}", result_to_string(result, &instance_name, run)).unwrap();
+            if let (Some(lower_bound), Some(gap)) = (result.lower_bound(), result.gap()) {
+                eprintln!("{}, run {}: lower bound {}, gap {:.2}%", instance_name, run, lower_bound, 100.0 * gap);
+            }
         }
-        let mut results = Vec::new();
-        for handle in handles.into_iter() {
-            results.push(handle.join().unwrap());
+        write_best_solution(&results, &instance_name, &args.run);
+        report.extend(results.iter().enumerate().map(|(run, result)| result_to_json(result, &instance_name, run)));
+        if args.run.stats {
+            eprint!("{}", stats_table(&results, &instance_name));
         }
-        results
-    }).unwrap()
-}
-
-fn run_instance(instance: Instance, timeout: usize) -> Result {
-    let test_iterations = 100000;
-    let mut sa = SimulatedAnnealing::new(Neighborhood::new(instance), SAParameters::default());
-    // Estimate iterations for timeout
-    sa.set_iterations(test_iterations);
-    let timer = Instant::now();
-    let (iterations, iterations_since_accept, iterations_since_improvement) = sa.solve();
-    let prep_time = Instant::now().duration_since(timer).as_millis() as usize;
-    if prep_time > timeout {
-        // timelimit already used up
-        return Result::new(sa.get_best().clone(), iterations, iterations_since_accept, iterations_since_improvement, Instant::now().duration_since(timer).as_secs() as usize);
     }
-    let iterations = timeout / prep_time * test_iterations;
-    sa.set_iterations(iterations);
-    sa.reset();
-
-    // Solve instance
-    let timer = Instant::now();
-    let (iterations, iterations_since_accept, iterations_since_improvement) = sa.solve();
+    write_report(&report, &args.run);
+}
 
-    Result::new(sa.get_best().clone(), iterations, iterations_since_accept, iterations_since_improvement, Instant::now().duration_since(timer).as_secs() as usize)
+fn bench(args: BenchArgs) {
+    args.run.validate();
+    let neighborhood = args.run.neighborhood_config();
+    let mut out = open_output(&args.run);
+    writeln!(out, "instance, min, avg, num_feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime").unwrap();
+    let mut report = Vec::new();
+    for path in cli::expand_instances(&args.instances) {
+        let instance_name = instance_name(&path);
+        let instance = match load_instance(&path) {
+            Ok(instance) => instance,
+            Err(err) => {
+                eprintln!("skipping {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        let warm_start = load_warm_start(&instance_name, &args.run);
+        let results = run_multithreaded(instance, warm_start, &neighborhood, args.runs, &args.run);
+        write_best_solution(&results, &instance_name, &args.run);
+        report.extend(results.iter().enumerate().map(|(run, result)| result_to_json(result, &instance_name, run)));
+        if args.run.stats {
+            eprint!("{}", stats_table(&results, &instance_name));
+        }
+        writeln!(out, "{}", results_to_string(results, &instance_name)).unwrap();
+    }
+    write_report(&report, &args.run);
 }
 
-fn results_to_string(results: Vec<Result>, instance: &str) -> String {
-    let num_feasible = results.iter().filter(|res| res.is_feasible()).count();
-    let iterations = results.iter().fold(0, |acc, res| acc + res.iterations) / results.len();
-    let iterations_since_accept = results.iter().fold(0, |acc, res| acc + res.iterations_since_accept()) / results.len();
-    let iterations_since_improvement = results.iter().fold(0, |acc, res| acc + res.iterations_since_improvement()) / results.len();
-    let runtime = results.iter().fold(0, |acc, res| acc + res.runtime()) / results.len();
+fn load_warm_start(instance_name: &str, args: &RunArgs) -> Option<Solution> {
+    let path = args.warm_start_file(instance_name)?;
+    match Solution::new_from_file(&path) {
+        Ok(solution) => Some(solution),
+        Err(err) => {
+            eprintln!("cannot read warm start {}: {}, starting from scratch", path.display(), err);
+            None
+        }
+    }
+}
 
-    if num_feasible == 0 {
-        return format!("{}, {}, {}, {}, {}, {}, {}, {}", instance, "-", "-", num_feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime);
+// Writes the best feasible solution over all runs to <solution dir>/<instance>.sol.json
+fn write_best_solution(results: &[SolveReport], instance_name: &str, args: &RunArgs) {
+    let dir = match args.solution_dir() {
+        Some(dir) => dir,
+        None => return
+    };
+    let best = results.iter().filter_map(|res| res.best()).min_by_key(|solution| solution.objective());
+    match best {
+        Some(solution) => {
+            let path = dir.join(format!("{}.sol.json", instance_name));
+            if let Err(err) = fs::create_dir_all(&dir).and_then(|_| solution.write_to_file(&path)) {
+                eprintln!("cannot write solution {}: {}", path.display(), err);
+            }
+        },
+        None => eprintln!("no feasible solution found for {}", instance_name)
     }
-    let obj_vals = results.iter().filter(|res| res.is_feasible()).map(|res| res.obj_val().unwrap()).collect::<Vec<usize>>();
-    let min = obj_vals.iter().min().unwrap();
-    let avg = obj_vals.iter().fold(0, |acc, time| acc + *time) / num_feasible;
-    
-    format!("{}, {}, {}, {}, {}, {}, {}, {}", instance, min, avg, num_feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime)
 }
 
-pub struct Result {
-    best: Option<(usize, State)>,
-    iterations: usize,
-    iterations_since_accept: usize,
-    iterations_since_improvement: usize,
-    runtime: usize
+fn write_report(report: &[serde_json::Value], args: &RunArgs) {
+    let path = match &args.report {
+        Some(path) => path,
+        None => return
+    };
+    if let Err(err) = fs::write(path, serde_json::to_string_pretty(report).unwrap()) {
+        eprintln!("cannot write report {}: {}", path.display(), err);
+    }
 }
 
-impl Result {
-    pub fn new(best: Option<(usize, State)>, iterations: usize, iterations_since_accept: usize, iterations_since_improvement: usize, runtime: usize) -> Self {
-        Result { best, iterations, iterations_since_accept, iterations_since_improvement, runtime }
+fn validate(args: ValidateArgs) {
+    let instance = load_instance(&args.instance)
+        .unwrap_or_else(|err| cli::exit_with_error(&format!("{}: {}", args.instance.display(), err)));
+    let solution = Solution::new_from_file(&args.solution)
+        .unwrap_or_else(|err| cli::exit_with_error(&format!("cannot read solution {}: {}", args.solution.display(), err)));
+    let report = validator::validate(&instance, &solution);
+    println!("objective: {}", report.objective());
+    for violation in report.violations().iter() {
+        println!("violation: {}", violation);
+    }
+    if !report.is_feasible() {
+        println!("{} violations", report.violations().len());
+        process::exit(1);
     }
+    println!("feasible");
+}
 
-    pub fn obj_val(&self) -> Option<usize> {
-        match self.best {
-            Some((obj_val, _)) => Some(obj_val),
-            None => None
-        }
+fn export_milp(args: ExportMilpArgs) {
+    let instance = load_instance(&args.instance)
+        .unwrap_or_else(|err| cli::exit_with_error(&format!("{}: {}", args.instance.display(), err)));
+    let model = milp::Model::new(&instance, args.step).unwrap_or_else(|err| cli::exit_with_error(&err.to_string()));
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path).unwrap_or_else(|err| cli::exit_with_error(&format!("cannot create {}: {}", path.display(), err))))),
+        None => Box::new(BufWriter::new(io::stdout()))
+    };
+    let result = match args.format {
+        MilpFormat::Lp => model.write_lp(&mut out),
+        MilpFormat::Mps => model.write_mps(&mut out)
+    };
+    if let Err(err) = result.and_then(|_| out.flush()) {
+        cli::exit_with_error(&format!("cannot write model: {}", err));
     }
+    eprintln!("time step {}, {} variables, {} constraints", model.step(), model.variables().len(), model.constraints().len());
+}
 
-    pub fn is_feasible(&self) -> bool {
-        self.best.is_some()
+// Loads an instance and reports the findings of its check that the solver tolerates
+fn load_instance(path: &Path) -> Result<Instance, InstanceError> {
+    let instance = Instance::new_from_file(path)?;
+    for warning in instance.warnings().iter() {
+        eprintln!("warning: {}: {}", path.display(), warning);
     }
 
-    pub fn iterations(&self) -> usize {
-        self.iterations
+    Ok(instance)
+}
+
+fn open_output(args: &RunArgs) -> Box<dyn Write> {
+    match &args.output {
+        Some(path) => Box::new(File::create(path).unwrap_or_else(|err| cli::exit_with_error(&format!("cannot create {}: {}", path.display(), err)))),
+        None => Box::new(io::stdout())
     }
+}
+
+fn instance_name(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().to_string()
+}
+
+fn run_multithreaded(instance: Instance, warm_start: Option<Solution>, neighborhood: &NeighborhoodConfig, runs: usize, args: &RunArgs) -> Vec<SolveReport> {
+    let next_run = AtomicUsize::new(0);
+    let incumbent = Arc::new(Incumbent::new());
+    let results = Mutex::new(Vec::with_capacity(runs));
+    thread::scope(|s| {
+        for _ in 0..args.threads(runs) {
+            s.spawn(|_| {
+                // Pick up runs until all are done
+                loop {
+                    let run = next_run.fetch_add(1, Ordering::SeqCst);
+                    if run >= runs { break; }
+
+                    let mut config = Config::new(args.sa.to_parameters(), args.time_limit(), args.seed(run))
+                        .with_neighborhood(neighborhood.clone())
+                        .with_algorithm(args.algorithm());
+                    if let Some(iterations) = args.iterations {
+                        config = config.with_iterations(iterations);
+                    }
+                    if let Some(solution) = &warm_start {
+                        config = config.with_warm_start(solution.clone());
+                    }
+                    if args.cooperative {
+                        config = config.with_incumbent(incumbent.clone());
+                    }
+                    let result = mdpsa::solve(&instance, &config);
+                    results.lock().unwrap().push((run, result));
+                }
+            });
+        }
+    }).unwrap();
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(run, _)| *run);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn result_to_string(result: &SolveReport, instance: &str, run: usize) -> String {
+    let obj_val = match result.obj_val() {
+        Some(obj_val) => obj_val.to_string(),
+        None => "-".to_string()
+    };
+
+    format!("{}, {}, {}, {}, {}, {}, {}, {}, {}", instance, run, result.seed(), obj_val, result.is_feasible(), result.iterations(), result.iterations_since_accept(), result.iterations_since_improvement(), result.runtime().as_secs())
+}
+
+fn result_to_json(result: &SolveReport, instance: &str, run: usize) -> serde_json::Value {
+    let operators = result.operator_weights().iter().zip(result.operator_stats().iter())
+        .map(|((name, weight), stats)| json!({
+            "name": name,
+            "weight": weight,
+            "calls": stats.calls(),
+            "no_ops": stats.no_ops(),
+            "accepts": stats.accepts(),
+            "improvements": stats.improvements(),
+            "new_bests": stats.new_bests(),
+            "mean_delta": stats.mean_delta(),
+            "time": stats.time().as_secs_f64()
+        }))
+        .collect::<Vec<serde_json::Value>>();
+
+    json!({
+        "instance": instance,
+        "run": run,
+        "seed": result.seed(),
+        "obj_val": result.obj_val(),
+        "iterations": result.iterations(),
+        "iterations_since_accept": result.iterations_since_accept(),
+        "iterations_since_improvement": result.iterations_since_improvement(),
+        "runtime": result.runtime().as_secs_f64(),
+        "initial_temperature": result.temperatures().0,
+        "final_temperature": result.temperatures().1,
+        "restarts": result.restarts(),
+        "exchanges": result.exchanges(),
+        "lower_bound": result.lower_bound(),
+        "gap": result.gap(),
+        "penalty_weights": {
+            "task": result.penalty_weights().task,
+            "maj_maint": result.penalty_weights().maj_maint,
+            "uncovered": result.penalty_weights().uncovered
+        },
+        "operators": operators
+    })
+}
 
-    pub fn iterations_since_improvement(&self) -> usize {
-        self.iterations_since_improvement
+// Operator statistics summed over all runs of an instance
+fn stats_table(results: &[SolveReport], instance: &str) -> String {
+    let mut stats = results.first().map_or_else(Vec::new, |result| result.operator_stats().clone());
+    for result in results.iter().skip(1) {
+        for (total, run_stats) in stats.iter_mut().zip(result.operator_stats().iter()) {
+            total.merge(run_stats);
+        }
     }
 
-    pub fn iterations_since_accept(&self) -> usize {
-        self.iterations_since_accept
+    let mut table = format!("{}\n{:<34} {:>10} {:>10} {:>10} {:>10} {:>10} {:>12} {:>9}\n", instance, "operator", "calls", "no-ops", "accepts", "improved", "new best", "mean delta", "time [s]");
+    for op in stats.iter() {
+        table += &format!("{:<34} {:>10} {:>10} {:>10} {:>10} {:>10} {:>12.1} {:>9.2}\n", op.name(), op.calls(), op.no_ops(), op.accepts(), op.improvements(), op.new_bests(), op.mean_delta(), op.time().as_secs_f64());
     }
 
-    pub fn runtime(&self) -> usize {
-        self.runtime
+    table
+}
+
+fn results_to_string(results: Vec<SolveReport>, instance: &str) -> String {
+    let num_feasible = results.iter().filter(|res| res.is_feasible()).count();
+    let runs = results.len().max(1);    // Averages of no runs are 0
+    let iterations = results.iter().fold(0, |acc, res| acc + res.iterations()) / runs;
+    let iterations_since_accept = results.iter().fold(0, |acc, res| acc + res.iterations_since_accept()) / runs;
+    let iterations_since_improvement = results.iter().fold(0, |acc, res| acc + res.iterations_since_improvement()) / runs;
+    let runtime = results.iter().fold(0, |acc, res| acc + res.runtime().as_secs()) / runs as u64;
+
+    if num_feasible == 0 {
+        return format!("{}, {}, {}, {}, {}, {}, {}, {}", instance, "-", "-", num_feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime);
     }
+    let obj_vals = results.iter().filter(|res| res.is_feasible()).map(|res| res.obj_val().unwrap()).collect::<Vec<usize>>();
+    let min = obj_vals.iter().min().unwrap();
+    let avg = obj_vals.iter().fold(0, |acc, time| acc + *time) / num_feasible;
+    
+    format!("{}, {}, {}, {}, {}, {}, {}, {}", instance, min, avg, num_feasible, iterations, iterations_since_accept, iterations_since_improvement, runtime)
 }
@@ -0,0 +1,155 @@
+use rand::{prelude::*, rngs::StdRng};
+
+use super::{simulated_annealing::{SimulatedAnnealing, Termination}, state::{JobToken, State}};
+
+
+/// Parameters of the memetic algorithm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GAParameters {
+    pub population: usize,
+    pub tournament: usize,          // Individuals compared to select a parent
+    pub mutation_moves: usize,      // Random operator moves applied to each child
+    pub local_iterations: usize     // Iterations of the annealing run improving each child
+}
+
+/// Steady-state genetic algorithm over states: children of two parents are mutated, improved by a short annealing run
+/// and replace the worst individual if they are better
+pub struct MemeticAlgorithm {
+    parameters: GAParameters,
+    population: Vec<State>,
+    local_search: SimulatedAnnealing,   // Reused for every child, its operator statistics cover the whole run
+    rng: StdRng,
+    iterations: usize,  // Iterations of all annealing runs
+    generations: usize,
+    best_feasible: Option<(usize, State)>
+}
+
+impl MemeticAlgorithm {
+    pub fn new(population: Vec<State>, local_search: SimulatedAnnealing, parameters: GAParameters, rng: StdRng) -> Self {
+        let mut ma = MemeticAlgorithm { parameters, population, local_search, rng, iterations: 0, generations: 0, best_feasible: None };
+        for i in 0..ma.population.len() {
+            ma.update_best(i);
+        }
+        ma
+    }
+
+    pub fn local_search(&self) -> &SimulatedAnnealing {
+        &self.local_search
+    }
+
+    pub fn population(&self) -> &Vec<State> {
+        &self.population
+    }
+
+    pub fn get_best(&self) -> &Option<(usize, State)> {
+        &self.best_feasible
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn generations(&self) -> usize {
+        self.generations
+    }
+
+    /// Improves the initial population, then breeds children until the termination, returns the annealing iterations
+    pub fn solve(&mut self, termination: Termination) -> usize {
+        for i in 0..self.population.len() {
+            if termination.is_reached(self.iterations) { break; }
+            let state = self.population[i].clone();
+            self.population[i] = self.improve(state, termination);
+            self.update_best(i);
+        }
+        while !termination.is_reached(self.iterations) {
+            self.generation(termination);
+        }
+
+        self.iterations
+    }
+
+    // Two parents by tournament, crossover, mutation, local search, the child replaces the worst individual
+    fn generation(&mut self, termination: Termination) {
+        self.generations += 1;
+        let first = self.select();
+        let second = self.select();
+        let mut child = crossover(&self.population[first], &self.population[second], &mut self.rng);
+        if self.parameters.mutation_moves > 0 {
+            self.local_search.set_state(child);
+            self.local_search.neighborhood_mut().perturb(self.parameters.mutation_moves);
+            child = self.local_search.neighborhood().state().clone();
+        }
+        let child = self.improve(child, termination);
+
+        // Children that are not better than the worst individual or equal to another one are dropped
+        let worst = (0..self.population.len()).max_by_key(|i| self.population[*i].working_obj_val()).unwrap();
+        let obj = child.working_obj_val();
+        if obj < self.population[worst].working_obj_val() && self.population.iter().all(|state| state.working_obj_val() != obj) {
+            self.population[worst] = child;
+            self.update_best(worst);
+        }
+    }
+
+    // Short annealing run from the state, the run keeps track of the best feasible state itself
+    fn improve(&mut self, state: State, termination: Termination) -> State {
+        let iterations = match termination {
+            Termination::Iterations(max_iterations) => self.parameters.local_iterations.min(max_iterations - self.iterations),
+            Termination::Deadline(_) => self.parameters.local_iterations
+        };
+        self.local_search.set_state(state);
+        self.local_search.solve(Termination::Iterations(iterations));
+        self.iterations += self.local_search.iterations();
+        if let Some((obj, state)) = self.local_search.get_best() {
+            if self.best_feasible.as_ref().is_none_or(|(best, _)| obj < best) {
+                self.best_feasible = Some((*obj, state.clone()));
+            }
+        }
+
+        self.local_search.neighborhood().state().clone()
+    }
+
+    fn select(&mut self) -> usize {
+        (0..self.parameters.tournament.max(1))
+            .map(|_| self.rng.gen_range(0..self.population.len()))
+            .min_by_key(|i| self.population[*i].working_obj_val())
+            .unwrap()
+    }
+
+    fn update_best(&mut self, idx: usize) {
+        self.population[idx].update_best(&mut self.best_feasible);
+    }
+}
+
+/// Child with the major maintenances of the first and the tasks (with their regular maintenances) of the second parent,
+/// jobs of the second parent that do not fit anymore are left out and conflicts are resolved by `State::repair`
+pub fn crossover(maintenance_parent: &State, task_parent: &State, rng: &mut StdRng) -> State {
+    let mut child = State::new(maintenance_parent.instance().clone(), *maintenance_parent.penalty_weights());
+    child.add_initial_penalties();
+    for res in 0..child.instance().resources() {
+        if maintenance_parent.assigned_maj_maint()[res] {
+            child.add_major_maintenance(res, maintenance_parent.maj_maint_ends()[res]);
+        }
+    }
+    for (res, jobs) in task_parent.jobs().iter().enumerate() {
+        for (time, job) in jobs.iter() {
+            match job {
+                JobToken::Task(task_id) => if child.can_add_task(res, *task_id) {
+                    child.add_task(res, *task_id);
+                },
+                JobToken::RegMaint => if child.can_add_maintenance(res, *time, false) {
+                    child.add_regular_maintenance(res, *time);
+                },
+                JobToken::MajMaint => ()
+            }
+        }
+    }
+    child.repair(rng);
+
+    child
+}
+
+impl Default for GAParameters {
+    fn default() -> Self {
+        GAParameters { population: 10, tournament: 2, mutation_moves: 10, local_iterations: 20000 }
+    }
+}
@@ -0,0 +1,342 @@
+use std::{error, fmt, io::{self, Write}};
+
+use crate::instance::Instance;
+
+
+/// Time-indexed MILP model of an instance, to be written as CPLEX LP or MPS file for external solvers.
+///
+/// Time is split into periods of `step` time units, maintenances start at the beginning of a period:
+/// - `x_t<task>_r<res>`: task assigned to resource (binary)
+/// - `mm_r<res>_s<period>`, `rm_r<res>_s<period>`: major / regular maintenance starts in period (binary)
+/// - `am_r<res>_p<period>`, `ar_r<res>_p<period>`: a major / regular maintenance is running in period (in [0, 1])
+/// - `u_p<period>_k<k>`: at least k maintenances are running in period (binary)
+///
+/// The objective sum over time of the squared number of running maintenances is `step * (2k - 1) * u_p_k` summed
+/// over all periods and levels k, which is exact since the cost of the levels increases with k.
+#[derive(Debug, Clone)]
+pub struct Model {
+    step: usize,
+    variables: Vec<Variable>,
+    objective: Vec<(usize, i64)>,
+    constraints: Vec<Constraint>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableKind {
+    Binary,
+    Continuous { upper: usize }    // Lower bound 0
+}
+
+#[derive(Debug, Clone)]
+pub struct Variable {
+    name: String,
+    kind: VariableKind
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sense {
+    LessEqual,
+    GreaterEqual,
+    Equal
+}
+
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    name: String,
+    terms: Vec<(usize, i64)>,
+    sense: Sense,
+    rhs: i64
+}
+
+/// Reason why a model cannot be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    InvalidStep { step: usize, duration_regular: usize, duration_major: usize }
+}
+
+impl Model {
+    /// Model with periods of `step` time units (default: the gcd of all times of the instance, for which the model is
+    /// exact). A coarser step must divide both maintenance durations, it restricts the maintenance start times and
+    /// treats tasks as occupying every period they touch, so its optimum is an upper bound.
+    pub fn new(instance: &Instance, step: Option<usize>) -> Result<Self, ModelError> {
+        let step = step.unwrap_or_else(|| time_step(instance));
+        if step == 0 || !instance.duration_regular().is_multiple_of(step) || !instance.duration_major().is_multiple_of(step) {
+            return Err(ModelError::InvalidStep { step, duration_regular: instance.duration_regular(), duration_major: instance.duration_major() });
+        }
+        let mut model = Model { step, variables: Vec::new(), objective: Vec::new(), constraints: Vec::new() };
+        let resources = instance.resources();
+        let maint_periods = instance.horizon() / step;     // Maintenances end within the horizon
+        let task_end = instance.tasks().iter().map(|task| task.end()).max().unwrap_or(0);
+        let periods = maint_periods.max(task_end.div_ceil(step));
+        let major_periods = instance.duration_major() / step;
+        let regular_periods = instance.duration_regular() / step;
+
+        let x = (0..instance.tasks().len()).map(|task_id| {
+            (0..resources).map(|res| model.add_variable(format!("x_t{}_r{}", task_id, res), VariableKind::Binary)).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        // Start variables for every period in which the maintenance still ends within the horizon
+        let starts = |length: usize| (instance.horizon() - length) / step + 1;
+        let mm = (0..resources).map(|res| {
+            (0..starts(instance.duration_major())).map(|s| model.add_variable(format!("mm_r{}_s{}", res, s), VariableKind::Binary)).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        let rm = (0..resources).map(|res| {
+            (0..starts(instance.duration_regular())).map(|s| model.add_variable(format!("rm_r{}_s{}", res, s), VariableKind::Binary)).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        let am = (0..resources).map(|res| {
+            (0..maint_periods).map(|p| model.add_variable(format!("am_r{}_p{}", res, p), VariableKind::Continuous { upper: 1 })).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        let ar = (0..resources).map(|res| {
+            (0..maint_periods).map(|p| model.add_variable(format!("ar_r{}_p{}", res, p), VariableKind::Continuous { upper: 1 })).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+        let u = (0..maint_periods).map(|p| {
+            (1..=resources).map(|k| model.add_variable(format!("u_p{}_k{}", p, k), VariableKind::Binary)).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        // Objective: squared number of running maintenances per period
+        for levels in u.iter() {
+            for (k, var) in levels.iter().enumerate() {
+                model.objective.push((*var, (step * (2 * k + 1)) as i64));
+            }
+        }
+
+        // Every task and every major maintenance is assigned exactly once
+        for (task_id, vars) in x.iter().enumerate() {
+            model.add_constraint(format!("assign_t{}", task_id), vars.iter().map(|var| (*var, 1)).collect(), Sense::Equal, 1);
+        }
+        for (res, vars) in mm.iter().enumerate() {
+            model.add_constraint(format!("major_r{}", res), vars.iter().map(|var| (*var, 1)).collect(), Sense::Equal, 1);
+        }
+
+        // Running maintenances: a(p) = a(p-1) + started in p - started `length` periods before p
+        for res in 0..resources {
+            for (name, active, start, length) in [("run_major", &am[res], &mm[res], major_periods), ("run_regular", &ar[res], &rm[res], regular_periods)] {
+                for p in 0..maint_periods {
+                    let mut terms = vec![(active[p], 1)];
+                    if p > 0 { terms.push((active[p-1], -1)); }
+                    if let Some(var) = start.get(p) { terms.push((*var, -1)); }
+                    if p >= length { terms.push((start[p-length], 1)); }
+                    model.add_constraint(format!("{}_r{}_p{}", name, res, p), terms, Sense::Equal, 0);
+                }
+            }
+        }
+
+        // At most one job per resource and period
+        for res in 0..resources {
+            for p in 0..periods {
+                let (start, end) = (p * step, (p + 1) * step);
+                let mut terms = instance.tasks().iter().enumerate()
+                    .filter(|(_, task)| task.start() < end && task.end() > start)
+                    .map(|(task_id, _)| (x[task_id][res], 1))
+                    .collect::<Vec<_>>();
+                if p < maint_periods {
+                    terms.push((am[res][p], 1));
+                    terms.push((ar[res][p], 1));
+                }
+                if terms.len() > 1 {
+                    model.add_constraint(format!("capacity_r{}_p{}", res, p), terms, Sense::LessEqual, 1);
+                }
+            }
+        }
+
+        // Major maintenances of different resources do not overlap, level indicators count the running maintenances
+        for p in 0..maint_periods {
+            model.add_constraint(format!("major_overlap_p{}", p), am.iter().map(|vars| (vars[p], 1)).collect(), Sense::LessEqual, 1);
+            let mut terms = u[p].iter().map(|var| (*var, 1)).collect::<Vec<_>>();
+            terms.extend(am.iter().chain(ar.iter()).map(|vars| (vars[p], -1)));
+            model.add_constraint(format!("count_p{}", p), terms, Sense::GreaterEqual, 0);
+        }
+
+        // Coverage: a task ending after time_regular needs a maintenance on its resource that ends at most
+        // time_regular before the end of the task and not after its start
+        for (task_id, task) in instance.tasks().iter().enumerate() {
+            if task.end() <= instance.time_regular() { continue; }
+            let earliest = task.end() - instance.time_regular();
+            let covers = |s: usize, length: usize| s * step + length >= earliest && s * step + length <= task.start();
+            for res in 0..resources {
+                let mut terms = vec![(x[task_id][res], 1)];
+                terms.extend(mm[res].iter().enumerate().filter(|(s, _)| covers(*s, instance.duration_major())).map(|(_, var)| (*var, -1)));
+                terms.extend(rm[res].iter().enumerate().filter(|(s, _)| covers(*s, instance.duration_regular())).map(|(_, var)| (*var, -1)));
+                model.add_constraint(format!("cover_t{}_r{}", task_id, res), terms, Sense::LessEqual, 0);
+            }
+        }
+
+        Ok(model)
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn variables(&self) -> &Vec<Variable> {
+        &self.variables
+    }
+
+    /// Coefficients of the minimized objective by variable index
+    pub fn objective(&self) -> &Vec<(usize, i64)> {
+        &self.objective
+    }
+
+    pub fn constraints(&self) -> &Vec<Constraint> {
+        &self.constraints
+    }
+
+    fn add_variable(&mut self, name: String, kind: VariableKind) -> usize {
+        self.variables.push(Variable { name, kind });
+        self.variables.len() - 1
+    }
+
+    fn add_constraint(&mut self, name: String, terms: Vec<(usize, i64)>, sense: Sense, rhs: i64) {
+        self.constraints.push(Constraint { name, terms, sense, rhs });
+    }
+
+    /// Writes the model in CPLEX LP format
+    pub fn write_lp(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "\\ Maintenance distribution problem, time step {}", self.step)?;
+        writeln!(out, "Minimize")?;
+        write!(out, " obj:")?;
+        self.write_lp_terms(out, &self.objective)?;
+        writeln!(out)?;
+        writeln!(out, "Subject To")?;
+        for constraint in self.constraints.iter() {
+            write!(out, " {}:", constraint.name)?;
+            self.write_lp_terms(out, &constraint.terms)?;
+            let sense = match constraint.sense {
+                Sense::LessEqual => "<=",
+                Sense::GreaterEqual => ">=",
+                Sense::Equal => "="
+            };
+            writeln!(out, " {} {}", sense, constraint.rhs)?;
+        }
+        writeln!(out, "Bounds")?;
+        for variable in self.variables.iter() {
+            if let VariableKind::Continuous { upper } = variable.kind {
+                writeln!(out, " 0 <= {} <= {}", variable.name, upper)?;
+            }
+        }
+        writeln!(out, "Binaries")?;
+        for variable in self.variables.iter().filter(|variable| variable.kind == VariableKind::Binary) {
+            writeln!(out, " {}", variable.name)?;
+        }
+        writeln!(out, "End")
+    }
+
+    // Terms with explicit signs, wrapped since LP readers limit the line length
+    fn write_lp_terms(&self, out: &mut impl Write, terms: &[(usize, i64)]) -> io::Result<()> {
+        if terms.is_empty() {
+            return write!(out, " 0 {}", self.variables[0].name);
+        }
+        for (i, (var, coef)) in terms.iter().enumerate() {
+            if i > 0 && i % 10 == 0 {
+                write!(out, "\n   ")?;
+            }
+            let sign = if *coef < 0 { '-' } else { '+' };
+            write!(out, " {} {} {}", sign, coef.abs(), self.variables[*var].name)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the model in free MPS format (names are longer than fixed MPS allows)
+    pub fn write_mps(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "NAME MDP")?;
+        writeln!(out, "ROWS")?;
+        writeln!(out, " N obj")?;
+        for constraint in self.constraints.iter() {
+            let sense = match constraint.sense {
+                Sense::LessEqual => 'L',
+                Sense::GreaterEqual => 'G',
+                Sense::Equal => 'E'
+            };
+            writeln!(out, " {} {}", sense, constraint.name)?;
+        }
+
+        // Coefficients are listed per variable
+        let mut columns = vec![Vec::new(); self.variables.len()];
+        for (var, coef) in self.objective.iter() {
+            columns[*var].push(("obj", *coef));
+        }
+        for constraint in self.constraints.iter() {
+            for (var, coef) in constraint.terms.iter() {
+                columns[*var].push((constraint.name.as_str(), *coef));
+            }
+        }
+        writeln!(out, "COLUMNS")?;
+        let mut integer = false;
+        for (variable, column) in self.variables.iter().zip(columns.iter()) {
+            let binary = variable.kind == VariableKind::Binary;
+            if binary != integer {
+                let marker = if binary { "INTORG" } else { "INTEND" };
+                writeln!(out, " MARKER 'MARKER' '{}'", marker)?;
+                integer = binary;
+            }
+            for (row, coef) in column.iter() {
+                writeln!(out, " {} {} {}", variable.name, row, coef)?;
+            }
+        }
+        if integer {
+            writeln!(out, " MARKER 'MARKER' 'INTEND'")?;
+        }
+
+        writeln!(out, "RHS")?;
+        for constraint in self.constraints.iter().filter(|constraint| constraint.rhs != 0) {
+            writeln!(out, " rhs {} {}", constraint.name, constraint.rhs)?;
+        }
+        writeln!(out, "BOUNDS")?;
+        for variable in self.variables.iter() {
+            match variable.kind {
+                VariableKind::Binary => writeln!(out, " BV bnd {}", variable.name)?,
+                VariableKind::Continuous { upper } => writeln!(out, " UP bnd {} {}", variable.name, upper)?
+            }
+        }
+        writeln!(out, "ENDATA")
+    }
+}
+
+/// Largest time step for which the model is exact: the gcd of all durations and times of the instance
+pub fn time_step(instance: &Instance) -> usize {
+    let mut times = vec![instance.duration_regular(), instance.duration_major(), instance.time_regular(), instance.horizon()];
+    times.extend(instance.tasks().iter().flat_map(|task| [task.start(), task.length()]));
+    times.into_iter().fold(0, gcd).max(1)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl Variable {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> VariableKind {
+        self.kind
+    }
+}
+
+impl Constraint {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn terms(&self) -> &Vec<(usize, i64)> {
+        &self.terms
+    }
+
+    pub fn sense(&self) -> Sense {
+        self.sense
+    }
+
+    pub fn rhs(&self) -> i64 {
+        self.rhs
+    }
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelError::InvalidStep { step, duration_regular, duration_major } =>
+                write!(f, "time step {} does not divide the maintenance durations {} and {}", step, duration_regular, duration_major)
+        }
+    }
+}
+
+impl error::Error for ModelError {}
@@ -18,17 +18,17 @@ impl AddMM {
 }
 
 impl NeighborhoodFunction for AddMM {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
 
-        let unassigned_mm = state.get_rand_unassigned_mm();
+        let unassigned_mm = state.get_rand_unassigned_mm(rng);
         if unassigned_mm.is_none() { return (0.0, change_tokens) }  // No unassigned mm
         
         let res = unassigned_mm.unwrap();
         
         // Add at random time:
-        let new_endtime = thread_rng().gen_range(state.instance().duration_major()..state.instance().horizon() + 1);
+        let new_endtime = rng.gen_range(state.instance().duration_major()..state.instance().horizon() + 1);
 
         // Remove all overlaps
         for (time, job) in state.get_overlaps(res,new_endtime - state.instance().duration_major(), new_endtime).iter() {
@@ -54,15 +54,15 @@ impl NeighborhoodFunction for AddMM {
         change_tokens.push(ChangeToken::AddMM(res));
         
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for AddMM {
-    fn to_string(&self) -> String {
-        format!("Add task ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for AddMM {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Add Major ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
@@ -20,11 +20,11 @@ impl AddTask {
 }
 
 impl NeighborhoodFunction for AddTask {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
 
-        let unassigned_task = state.get_rand_unassigned_task();
+        let unassigned_task = state.get_rand_unassigned_task(rng);
         if unassigned_task.is_none() { return (0.0, change_tokens) }  // No unassigned task
         
         let task_id = unassigned_task.unwrap();
@@ -32,7 +32,7 @@ impl NeighborhoodFunction for AddTask {
         
         if self.greedy {
             let mut order = (0..state.instance().resources()).collect::<Vec<usize>>();
-            order.shuffle(&mut thread_rng());
+            order.shuffle(rng);
             for &res in order.iter() {
                 if state.can_add_task(res, task_id) {
                     state.add_task(res, task_id);
@@ -42,7 +42,7 @@ impl NeighborhoodFunction for AddTask {
             }
         } else {
             // Add to random resource:
-            let res = thread_rng().gen_range(0..state.instance().resources());
+            let res = rng.gen_range(0..state.instance().resources());
             // Remove all overlaps
             let overlaps = state.get_overlaps(res, task.start(), task.end());
             for (time, job) in overlaps.iter() {
@@ -68,15 +68,15 @@ impl NeighborhoodFunction for AddTask {
         }
         
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for AddTask {
-    fn to_string(&self) -> String {
-        format!("Add task ({}, {})", if self.greedy { "greedy"} else { "random" }, if self.repair { "rep"} else { "norep" })
+impl fmt::Display for AddTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Add task ({}, {})", if self.greedy { "greedy"} else { "random" }, if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::{error, fmt, fs, io, path::Path};
+
+use crate::adaptive_weights::AdaptionParameters;
+use super::*;
+
+
+/// Operators used by the `Neighborhood` and their selection weights
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NeighborhoodConfig {
+    operators: Vec<OperatorConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    adaptive: Option<AdaptionParameters>    // Adapt the weights during the run, static weights if not set
+}
+
+/// Reason why an operator pool cannot be used
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    InvalidWeights,     // Negative or non-finite weight, or no positive weight at all
+    InvalidAdaption     // segment_length 0, reaction outside [0, 1] or min_weight not positive and finite
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OperatorConfig {
+    #[serde(flatten)]
+    operator: Operator,
+    #[serde(default = "default_weight")]
+    weight: f64
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "operator", rename_all = "snake_case")]
+pub enum Operator {
+    MoveRm { repair: bool, max_move: usize },
+    MoveMm { repair: bool, max_move: usize },
+    RemoveRm { repair: bool },
+    MoveRmToAny { repair: bool },
+    CoverTask { greedy: bool },
+    MoveMmToAny { repair: bool },
+    AddTask { greedy: bool, repair: bool },
+    RemoveTask,
+    AddMm { repair: bool },
+    MoveTask { repair: bool },
+    MoveMmDestructive { repair: bool },
+    SwapMm { repair: bool }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl NeighborhoodConfig {
+    /// Pool with static weights, at least one weight must be positive and none negative
+    pub fn new(operators: Vec<OperatorConfig>) -> Result<Self, ConfigError> {
+        if !operators.iter().any(|op| op.weight > 0.0) || operators.iter().any(|op| op.weight < 0.0 || !op.weight.is_finite()) {
+            return Err(ConfigError::InvalidWeights);
+        }
+
+        Ok(NeighborhoodConfig { operators, adaptive: None })
+    }
+
+    /// Start from the configured weights and adapt them to the scores the operators earn
+    pub fn with_adaptive(mut self, parameters: AdaptionParameters) -> Result<Self, ConfigError> {
+        if parameters.segment_length == 0 || !(0.0..=1.0).contains(&parameters.reaction) || !parameters.min_weight.is_finite() || parameters.min_weight <= 0.0 {
+            return Err(ConfigError::InvalidAdaption);
+        }
+        self.adaptive = Some(parameters);
+
+        Ok(self)
+    }
+
+    pub fn new_from_file(path: &Path) -> io::Result<Self> {
+        let file: NeighborhoodConfig = serde_json::from_str(&fs::read_to_string(path)?)?;
+        // Deserializing bypasses the checks of the constructors
+        let config = NeighborhoodConfig::new(file.operators).and_then(|config| match file.adaptive {
+            Some(parameters) => config.with_adaptive(parameters),
+            None => Ok(config)
+        });
+
+        config.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn operators(&self) -> &Vec<OperatorConfig> {
+        &self.operators
+    }
+
+    pub fn adaptive(&self) -> Option<&AdaptionParameters> {
+        self.adaptive.as_ref()
+    }
+}
+
+impl Default for NeighborhoodConfig {
+    fn default() -> Self {
+        let operators = vec![
+            Operator::MoveRm { repair: true, max_move: 100 },
+            Operator::MoveMm { repair: true, max_move: 100 },
+            Operator::RemoveRm { repair: true },
+            Operator::MoveRmToAny { repair: true },
+            Operator::CoverTask { greedy: false },
+            Operator::CoverTask { greedy: true },
+            Operator::MoveMmToAny { repair: true },
+            Operator::AddTask { greedy: false, repair: true },
+            Operator::AddTask { greedy: true, repair: true },
+            // RemoveTask seems to make it significantly worse, check again with working increasing penalties
+            Operator::AddMm { repair: true },
+            Operator::MoveTask { repair: true },
+            Operator::MoveMmDestructive { repair: true },
+            Operator::SwapMm { repair: true },
+        ];
+
+        NeighborhoodConfig { operators: operators.into_iter().map(|operator| OperatorConfig::new(operator, default_weight())).collect(), adaptive: None }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::InvalidWeights => write!(f, "operator weights must be finite and non-negative with at least one positive weight"),
+            ConfigError::InvalidAdaption => write!(f, "adaptive selection needs segment_length > 0, reaction in [0, 1] and min_weight > 0")
+        }
+    }
+}
+
+impl error::Error for ConfigError {}
+
+impl OperatorConfig {
+    pub fn new(operator: Operator, weight: f64) -> Self {
+        OperatorConfig { operator, weight }
+    }
+
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+impl Operator {
+    pub fn build(&self) -> Box<dyn NeighborhoodFunction> {
+        match *self {
+            Operator::MoveRm { repair, max_move } => Box::new(MoveRM::new(repair, max_move)),
+            Operator::MoveMm { repair, max_move } => Box::new(MoveMM::new(repair, max_move)),
+            Operator::RemoveRm { repair } => Box::new(RemoveRM::new(repair)),
+            Operator::MoveRmToAny { repair } => Box::new(MoveRMToAny::new(repair)),
+            Operator::CoverTask { greedy } => Box::new(CoverTask::new(greedy)),
+            Operator::MoveMmToAny { repair } => Box::new(MoveMMToAny::new(repair)),
+            Operator::AddTask { greedy, repair } => Box::new(AddTask::new(greedy, repair)),
+            Operator::RemoveTask => Box::new(RemoveTask::new()),
+            Operator::AddMm { repair } => Box::new(AddMM::new(repair)),
+            Operator::MoveTask { repair } => Box::new(MoveTask::new(repair)),
+            Operator::MoveMmDestructive { repair } => Box::new(MoveMMDestructive::new(repair)),
+            Operator::SwapMm { repair } => Box::new(SwapMM::new(repair))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted(weights: &[f64]) -> Vec<OperatorConfig> {
+        weights.iter().map(|weight| OperatorConfig::new(Operator::RemoveRm { repair: true }, *weight)).collect()
+    }
+
+    #[test]
+    fn accepts_finite_weights() {
+        assert!(NeighborhoodConfig::new(weighted(&[0.0, 2.5])).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_weights() {
+        for weights in [vec![], vec![0.0], vec![1.0, -1.0], vec![1.0, f64::NAN], vec![1.0, f64::INFINITY]] {
+            assert_eq!(NeighborhoodConfig::new(weighted(&weights)).unwrap_err(), ConfigError::InvalidWeights, "{:?}", weights);
+        }
+    }
+}
@@ -13,10 +13,10 @@ impl CoverTask {
 }
 
 impl NeighborhoodFunction for CoverTask {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let uncovered_task = state.get_rand_uncovered_task();
+        let uncovered_task = state.get_rand_uncovered_task(rng);
         if uncovered_task.is_none() { return (0.0, change_tokens) }  // No uncovered task
         
         let (res, time) = uncovered_task.unwrap();
@@ -39,8 +39,8 @@ impl NeighborhoodFunction for CoverTask {
     }
 }
 
-impl ToString for CoverTask {
-    fn to_string(&self) -> String {
-        format!("Cover task ({})", if self.greedy { "greedy"} else { "random" })
+impl fmt::Display for CoverTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cover task ({})", if self.greedy { "greedy"} else { "random" })
     }
 }
\ No newline at end of file
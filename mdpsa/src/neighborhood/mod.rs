@@ -1,4 +1,7 @@
+#[allow(clippy::module_inception)]
 mod neighborhood;
+mod config;
+mod stats;
 mod move_mm;
 mod move_mm_to_any;
 mod move_rm;
@@ -13,6 +16,8 @@ mod move_mm_destructive;
 mod swap_mm;
 
 use super::{State, Instance};
+use rand::rngs::StdRng;
+use std::fmt;
 
 use move_mm::MoveMM;
 use move_mm_to_any::MoveMMToAny;
@@ -21,7 +26,7 @@ use move_rm_to_any::MoveRMToAny;
 use remove_rm::RemoveRM;
 use cover_task::CoverTask;
 use add_task::AddTask;
-// use remove_task::RemoveTask;
+use remove_task::RemoveTask;
 use add_mm::AddMM;
 use move_task::MoveTask;
 use move_mm_destructive::MoveMMDestructive;
@@ -29,11 +34,14 @@ use swap_mm::SwapMM;
 
 
 pub use neighborhood::Neighborhood;
+pub use config::{ConfigError, NeighborhoodConfig, Operator, OperatorConfig};
+pub use stats::OperatorStats;
 
-pub trait NeighborhoodFunction: ToString {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>);
+pub trait NeighborhoodFunction: fmt::Display + Send {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>);
 }
 
+#[derive(Debug, Clone)]
 pub enum ChangeToken {
     MovedRM(usize, usize, usize),   // (res, prev, new)
     AddRM(usize, usize),   // (res, time)
@@ -52,12 +60,26 @@ pub enum PenaltyToken {
     RegMaintNotCovered(usize)   // Task (partially) uncovered (length that is uncovered)
 }
 
+/// Integer weights of the penalty types, so the penalty stays exact under incremental updates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PenaltyWeights {
+    pub task: usize,        // Per time unit of unassigned tasks
+    pub maj_maint: usize,   // Per time unit of unassigned maj maintenances
+    pub uncovered: usize    // Per uncovered time unit
+}
+
 impl PenaltyToken {
-    pub fn to_penalty(&self, instance: &Instance, multi: usize) -> usize {
-        instance.resources() * multi * match self {
-            PenaltyToken::MajMaint => instance.duration_major(),
-            PenaltyToken::Task(i) => instance.tasks()[*i].length(),
-            PenaltyToken::RegMaintNotCovered(x) => *x,
+    pub fn to_penalty(&self, instance: &Instance, weights: &PenaltyWeights) -> usize {
+        instance.resources() * match self {
+            PenaltyToken::MajMaint => weights.maj_maint * instance.duration_major(),
+            PenaltyToken::Task(i) => weights.task * instance.tasks()[*i].length(),
+            PenaltyToken::RegMaintNotCovered(x) => weights.uncovered * *x,
         }
     }
 }
+
+impl Default for PenaltyWeights {
+    fn default() -> Self {
+        PenaltyWeights { task: 1, maj_maint: 1, uncovered: 1 }
+    }
+}
@@ -15,10 +15,10 @@ impl MoveMM {
 }
 
 impl NeighborhoodFunction for MoveMM {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let mm = state.get_rand_mm();
+        let mm = state.get_rand_mm(rng);
         if mm.is_none() { return (0.0, change_tokens) }  // No major maintenance
         
         let (res, time) = mm.unwrap();
@@ -28,7 +28,7 @@ impl NeighborhoodFunction for MoveMM {
         // Get new random time and add MM
         let left = cmp::max(left, time-self.max_move);
         let right = cmp::min(right, time+self.max_move);
-        let new_time = thread_rng().gen_range(left..right+1);
+        let new_time = rng.gen_range(left..right+1);
 
         // Replace maj maintenance
         state.remove_major_maintenance(res);
@@ -37,15 +37,15 @@ impl NeighborhoodFunction for MoveMM {
         
         // Repair a task that was uncovered due to move
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for MoveMM {
-    fn to_string(&self) -> String {
-        format!("Move Major ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for MoveMM {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move Major ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
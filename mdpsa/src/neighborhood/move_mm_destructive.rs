@@ -14,15 +14,15 @@ impl MoveMMDestructive {
 }
 
 impl NeighborhoodFunction for MoveMMDestructive {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let mm = state.get_rand_mm();
+        let mm = state.get_rand_mm(rng);
         if mm.is_none() { return (0.0, change_tokens) }  // No major maintenance assigned
 
         let (res, mm_time) = mm.unwrap();
         // Add at random time:
-        let new_endtime = thread_rng().gen_range(state.instance().duration_major()..state.instance().horizon() + 1);
+        let new_endtime = rng.gen_range(state.instance().duration_major()..state.instance().horizon() + 1);
 
         // Remove old mm
         state.remove_major_maintenance(res);
@@ -54,15 +54,15 @@ impl NeighborhoodFunction for MoveMMDestructive {
         
         // Repair a task that was uncovered due to move
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for MoveMMDestructive {
-    fn to_string(&self) -> String {
-        format!("Move Major destructively ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for MoveMMDestructive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move Major destructively ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
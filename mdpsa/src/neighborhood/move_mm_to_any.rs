@@ -11,14 +11,14 @@ impl MoveMMToAny {
 }
 
 impl NeighborhoodFunction for MoveMMToAny {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let mm = state.get_rand_mm();
+        let mm = state.get_rand_mm(rng);
         if mm.is_none() { return (0.0, change_tokens) }  // No major maintenance assigned
 
         let (res, time) = mm.unwrap();
-        let new_time = state.can_add_mm_without_destruction(res);
+        let new_time = state.can_add_mm_without_destruction(res, rng);
         if new_time.is_none() { return (0.0, change_tokens) }
 
         // Replace reg maintenance
@@ -28,15 +28,15 @@ impl NeighborhoodFunction for MoveMMToAny {
         
         // Repair a task that was uncovered due to move
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for MoveMMToAny {
-    fn to_string(&self) -> String {
-        format!("Move Major To Any ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for MoveMMToAny {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move Major To Any ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
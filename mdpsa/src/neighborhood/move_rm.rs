@@ -15,10 +15,10 @@ impl MoveRM {
 }
 
 impl NeighborhoodFunction for MoveRM {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let rm = state.get_rand_rm();
+        let rm = state.get_rand_rm(rng);
         if rm.is_none() { return (0.0, change_tokens) }  // No regular maintenance
 
         let (res, time) = rm.unwrap();
@@ -28,7 +28,7 @@ impl NeighborhoodFunction for MoveRM {
         // Get new random time and add RM
         let left = cmp::max(left, time-self.max_move);
         let right = cmp::min(right, time+self.max_move);
-        let new_time = thread_rng().gen_range(left..right+1);
+        let new_time = rng.gen_range(left..right+1);
         
         // Replace reg maintenance
         state.remove_regular_maintenance(res, time);
@@ -37,15 +37,15 @@ impl NeighborhoodFunction for MoveRM {
         
         // Repair a task that was uncovered due to move
         if self.repair { 
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for MoveRM {
-    fn to_string(&self) -> String {
-        format!("Move Regular ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for MoveRM {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move Regular ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
@@ -12,10 +12,10 @@ impl MoveRMToAny {
 }
 
 impl NeighborhoodFunction for MoveRMToAny {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let rm = state.get_rand_rm();
+        let rm = state.get_rand_rm(rng);
         if rm.is_none() { return (0.0, change_tokens) }  // No regular maintenance
 
         let (res, time) = rm.unwrap();
@@ -24,8 +24,7 @@ impl NeighborhoodFunction for MoveRMToAny {
         if windows.is_empty() { return (0.0, change_tokens) } // Cannot move selected RM
 
         // Get new random time and add RM
-        let mut rng = thread_rng();
-        let (left, right) = windows.choose(&mut rng).unwrap();
+        let (left, right) = windows.choose(rng).unwrap();
         let new_time = rng.gen_range(*left..*right+1);
 
         // Replace reg maintenance
@@ -35,15 +34,15 @@ impl NeighborhoodFunction for MoveRMToAny {
         
         // Repair a task that was uncovered due to move
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for MoveRMToAny {
-    fn to_string(&self) -> String {
-        format!("Move Regular To Any ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for MoveRMToAny {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move Regular To Any ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
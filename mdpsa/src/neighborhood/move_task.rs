@@ -18,17 +18,17 @@ impl MoveTask {
 }
 
 impl NeighborhoodFunction for MoveTask {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
 
-        let assigned_task = state.get_rand_assigned_task();
+        let assigned_task = state.get_rand_assigned_task(rng);
         if assigned_task.is_none() { return (0.0, change_tokens) }  // No assigned task
         
         let (prev_res, task_id) = assigned_task.unwrap();
         let task = &state.instance().tasks()[task_id];
         
-        let mut res = thread_rng().gen_range(0..state.instance().resources() - 1);
+        let mut res = rng.gen_range(0..state.instance().resources() - 1);
         if res >= prev_res {
             res += 1;   // Shift to viable res idx
         }
@@ -59,15 +59,15 @@ impl NeighborhoodFunction for MoveTask {
         change_tokens.push(ChangeToken::AddTask(task_id));
         
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for MoveTask {
-    fn to_string(&self) -> String {
-        format!("Move task ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for MoveTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move task ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
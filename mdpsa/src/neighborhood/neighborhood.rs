@@ -1,23 +1,45 @@
-use crate::instance::Instance;
+use crate::{adaptive_weights::{AdaptiveWeights, MoveOutcome}, instance::Instance};
 
 use super::State;
 use super::*;
-use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::time::Instant;
 
 
+/// Current state of the search, applies random operators and rolls back rejected moves
 pub struct Neighborhood {
     state: State,
-    last_changes: Vec<ChangeToken>
+    last_changes: Vec<ChangeToken>,
+    rng: StdRng,
+    operators: Vec<Box<dyn NeighborhoodFunction>>,
+    selection: AdaptiveWeights,
+    last_operator: usize,
+    stats: Vec<OperatorStats>
 }
 
 impl Neighborhood {
-    pub fn new(instance: Instance) -> Self {
-        let mut state = State::new(instance, 1);
-        state.initialize();
+    pub fn new(instance: Instance, mut rng: StdRng, config: &NeighborhoodConfig) -> Self {
+        let mut state = State::new(instance, PenaltyWeights::default());
+        state.initialize(&mut rng);
+
+        Neighborhood::from_state(state, rng, config)
+    }
+
+    // Warm start from a given state (e.g. built from a previous solution)
+    pub fn from_state(state: State, rng: StdRng, config: &NeighborhoodConfig) -> Self {
+        let operators: Vec<Box<dyn NeighborhoodFunction>> = config.operators().iter().map(|op| op.operator().build()).collect();
+        let stats = operators.iter().map(|op| OperatorStats::new(op.to_string())).collect();
+        let weights = config.operators().iter().map(|op| op.weight()).collect();
+        let selection = AdaptiveWeights::new(weights, config.adaptive().cloned());
 
         Neighborhood { 
             state,
-            last_changes: Vec::new()
+            last_changes: Vec::new(),
+            rng,
+            operators,
+            selection,
+            last_operator: 0,
+            stats
         }
     }
 
@@ -25,68 +47,132 @@ impl Neighborhood {
         &self.state
     }
 
+    // Single source of randomness of a run, so a run can be replayed from its seed
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Changes of the last move that was neither accepted nor rejected yet
+    pub fn last_changes(&self) -> &Vec<ChangeToken> {
+        &self.last_changes
+    }
+
+    /// Name and current selection weight of each operator
+    pub fn operator_weights(&self) -> Vec<(String, f64)> {
+        self.operators.iter().zip(self.selection.weights().iter()).map(|(op, weight)| (op.to_string(), *weight)).collect()
+    }
+
+    /// Call and outcome counters of each operator
+    pub fn operator_stats(&self) -> &Vec<OperatorStats> {
+        &self.stats
+    }
+
     pub fn get_next(&mut self) -> f64 {
-        let neighborhoods: Vec<Box<dyn NeighborhoodFunction>> = vec![
-            // Box::new(MoveRM::new(false, 100)),  // norep
-            Box::new(MoveRM::new(true, 100)),
-            // Box::new(MoveMM::new(false, 100)),  // norep
-            Box::new(MoveMM::new(true, 100)),
-            // Box::new(RemoveRM::new(false)), // norep
-            Box::new(RemoveRM::new(true)),
-            // Box::new(MoveRMToAny::new(false)),  // norep
-            Box::new(MoveRMToAny::new(true)),
-            Box::new(CoverTask::new(false)),    
-            Box::new(CoverTask::new(true)),     // greedy
-            // Box::new(MoveMMToAny::new(false)),  // norep
-            Box::new(MoveMMToAny::new(true)),
-            // Box::new(AddTask::new(false, false)),   // norep
-            Box::new(AddTask::new(false, true)),    
-            // Box::new(AddTask::new(true, false)),    // greedy, norep
-            Box::new(AddTask::new(true, true)),     // greedy
-            // Box::new(RemoveTask::new()), // Seems to make it significantly worse, check again with working increasing penalties
-            // Box::new(AddMM::new(false)),    // norep
-            Box::new(AddMM::new(true)),
-            // Box::new(MoveTask::new(false)),  // norep
-            Box::new(MoveTask::new(true)),
-            // Box::new(MoveMMDestructive::new(false)),  // norep
-            Box::new(MoveMMDestructive::new(true)),
-            // Box::new(SwapMM::new(false)),     // norep
-            Box::new(SwapMM::new(true)),
-        ];
-        let selected = neighborhoods.choose(&mut thread_rng()).unwrap();
-        let (delta, tokens) = selected.get_neighbor(&mut self.state);
+        let operator = self.select_operator();
+        self.apply(operator)
+    }
+
+    /// Draws an operator by the selection weights
+    pub fn select_operator(&mut self) -> usize {
+        self.selection.select(&mut self.rng)
+    }
+
+    /// Applies the given operator, its changes are kept until the move is accepted or rejected
+    pub fn apply(&mut self, operator: usize) -> f64 {
+        let timer = Instant::now();
+        let delta = self.replay(operator);
+        self.stats[self.last_operator].record_call(delta, self.last_changes.is_empty(), timer.elapsed());
+
+        delta
+    }
+
+    /// Applies the given operator like `apply` without counting the call, e.g. to make a sampled move that was discarded
+    /// (with the generator state it was sampled with)
+    pub fn replay(&mut self, operator: usize) -> f64 {
+        self.last_operator = operator;
+        let selected = &self.operators[self.last_operator];
+        let (delta, tokens) = selected.get_neighbor(&mut self.state, &mut self.rng);
         self.last_changes = tokens;
-        
+
         delta
     }
 
-    pub fn accept(&mut self) {
+    // The outcome is credited to the operator of the last move, moves that changed nothing earn no score
+    pub fn accept(&mut self, outcome: MoveOutcome) {
+        let outcome = if self.last_changes.is_empty() { MoveOutcome::Rejected } else { outcome };
+        self.stats[self.last_operator].record_outcome(outcome);
+        self.selection.record(self.last_operator, outcome);
         self.last_changes = Vec::new();
     }
 
     pub fn reject(&mut self) {
-        for token in self.last_changes.iter().rev() {
-            match token {
-                ChangeToken::MovedRM(res, prev, new) => {
-                    self.state.remove_regular_maintenance(*res, *new);
-                    self.state.add_regular_maintenance(*res, *prev);
-                },
-                ChangeToken::AddRM(res, new_rm) => self.state.remove_regular_maintenance(*res, *new_rm),
-                ChangeToken::RemoveRM(res, time) => self.state.add_regular_maintenance(*res, *time),
-                ChangeToken::MovedMM(res, prev) => {
-                    self.state.remove_major_maintenance(*res);
-                    self.state.add_major_maintenance(*res, *prev);
-                },
-                ChangeToken::AddMM(res) => self.state.remove_major_maintenance(*res),
-                ChangeToken::RemoveMM(res, time) => self.state.add_major_maintenance(*res, *time),
-                ChangeToken::AddTask(task_id) => self.state.remove_task(*task_id),
-                ChangeToken::RemoveTask(res, task_id) => self.state.add_task(*res, *task_id),
+        self.selection.record(self.last_operator, MoveOutcome::Rejected);
+        self.roll_back();
+    }
+
+    /// Rolls back the last move without crediting an outcome, for sampled moves that are decided on later
+    pub fn discard(&mut self) {
+        self.roll_back();
+    }
+
+    /// Credits a rejection to the operator of a discarded move that was not made
+    pub fn record_rejected(&mut self, operator: usize) {
+        self.selection.record(operator, MoveOutcome::Rejected);
+    }
+
+    /// Continue from another state, keeps the current penalty weights
+    pub fn set_state(&mut self, mut state: State) {
+        if state.penalty_weights() != self.state.penalty_weights() {
+            state.set_penalty_weights(*self.state.penalty_weights());
+        }
+        self.state = state;
+        self.last_changes = Vec::new();
+    }
+
+    /// Continue from a new random initial state
+    pub fn reinitialize(&mut self) {
+        let mut state = State::new(self.state.instance().clone(), *self.state.penalty_weights());
+        state.initialize(&mut self.rng);
+        self.set_state(state);
+    }
+
+    /// Applies random moves regardless of their delta (not counted in the statistics)
+    pub fn perturb(&mut self, moves: usize) {
+        for _ in 0..moves {
+            let selected = &self.operators[self.selection.select(&mut self.rng)];
+            selected.get_neighbor(&mut self.state, &mut self.rng);
+        }
+        self.last_changes = Vec::new();
+    }
+
+    /// Deltas of random moves from the current state, every move is rolled back (not counted in the statistics)
+    pub fn sample_deltas(&mut self, samples: usize) -> Vec<f64> {
+        let mut deltas = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let selected = &self.operators[self.selection.select(&mut self.rng)];
+            let (delta, tokens) = selected.get_neighbor(&mut self.state, &mut self.rng);
+            if !tokens.is_empty() {
+                deltas.push(delta);
             }
+            self.last_changes = tokens;
+            self.roll_back();
         }
+
+        deltas
+    }
+
+    /// Reverts changes of already accepted moves (in reverse order)
+    pub fn undo(&mut self, changes: &[ChangeToken]) {
+        self.state.undo_changes(changes);
+        self.last_changes = Vec::new();
+    }
+
+    fn roll_back(&mut self) {
+        self.state.undo_changes(&self.last_changes);
         self.last_changes = Vec::new()
     }
 
-    // pub fn increase_penalty_multi(&mut self) {
-    //     self.state.increase_penalty_multi();
-    // }
+    pub fn set_penalty_weights(&mut self, penalty_weights: PenaltyWeights) {
+        self.state.set_penalty_weights(penalty_weights);
+    }
 }
@@ -11,10 +11,10 @@ impl RemoveRM {
 }
 
 impl NeighborhoodFunction for RemoveRM {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let rm = state.get_rand_rm();
+        let rm = state.get_rand_rm(rng);
         if rm.is_none() { return (0.0, change_tokens) }  // No regular maintenance
 
         let (res, time) = rm.unwrap();
@@ -23,15 +23,15 @@ impl NeighborhoodFunction for RemoveRM {
         
         // Repair a task that was uncovered due to move
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for RemoveRM {
-    fn to_string(&self) -> String {
-        format!("Remove Regular ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for RemoveRM {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Remove Regular ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
@@ -9,11 +9,11 @@ impl RemoveTask {
 }
 
 impl NeighborhoodFunction for RemoveTask {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
 
-        let assigned_task = state.get_rand_assigned_task();
+        let assigned_task = state.get_rand_assigned_task(rng);
         if assigned_task.is_none() { return (0.0, change_tokens) }  // No assigned task
 
         let (res, task_id) = assigned_task.unwrap();
@@ -24,8 +24,8 @@ impl NeighborhoodFunction for RemoveTask {
     }
 }
 
-impl ToString for RemoveTask {
-    fn to_string(&self) -> String {
-        format!("Remove task")
+impl fmt::Display for RemoveTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Remove task")
     }
 }
\ No newline at end of file
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use crate::adaptive_weights::MoveOutcome;
+
+
+/// Counters of a single operator over a run
+#[derive(Debug, Clone, Default)]
+pub struct OperatorStats {
+    name: String,
+    calls: usize,
+    no_ops: usize,      // Calls that did not change the state
+    accepts: usize,
+    improvements: usize,
+    new_bests: usize,
+    delta_sum: f64,     // Over all calls that changed the state
+    time: Duration
+}
+
+impl OperatorStats {
+    pub fn new(name: String) -> Self {
+        OperatorStats { name, ..Default::default() }
+    }
+
+    pub fn record_call(&mut self, delta: f64, no_op: bool, time: Duration) {
+        self.calls += 1;
+        self.time += time;
+        if no_op {
+            self.no_ops += 1;
+        } else {
+            self.delta_sum += delta;
+        }
+    }
+
+    pub fn record_outcome(&mut self, outcome: MoveOutcome) {
+        match outcome {
+            MoveOutcome::Rejected => (),
+            MoveOutcome::Accepted => self.accepts += 1,
+            MoveOutcome::Improved => {
+                self.accepts += 1;
+                self.improvements += 1;
+            },
+            MoveOutcome::NewBest => {
+                self.accepts += 1;
+                self.improvements += 1;
+                self.new_bests += 1;
+            }
+        }
+    }
+
+    // Sum of the counters, e.g. of the same operator over several runs
+    pub fn merge(&mut self, other: &OperatorStats) {
+        self.calls += other.calls;
+        self.no_ops += other.no_ops;
+        self.accepts += other.accepts;
+        self.improvements += other.improvements;
+        self.new_bests += other.new_bests;
+        self.delta_sum += other.delta_sum;
+        self.time += other.time;
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    pub fn no_ops(&self) -> usize {
+        self.no_ops
+    }
+
+    pub fn accepts(&self) -> usize {
+        self.accepts
+    }
+
+    pub fn improvements(&self) -> usize {
+        self.improvements
+    }
+
+    pub fn new_bests(&self) -> usize {
+        self.new_bests
+    }
+
+    /// Mean delta of the calls that changed the state
+    pub fn mean_delta(&self) -> f64 {
+        let changes = self.calls - self.no_ops;
+        if changes == 0 { 0.0 } else { self.delta_sum / changes as f64 }
+    }
+
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+}
@@ -14,17 +14,17 @@ impl SwapMM {
 }
 
 impl NeighborhoodFunction for SwapMM {
-    fn get_neighbor(&self, state: &mut State) -> (f64, Vec<ChangeToken>) {
+    fn get_neighbor(&self, state: &mut State, rng: &mut StdRng) -> (f64, Vec<ChangeToken>) {
         let obj_prev = state.working_obj_val();
         let mut change_tokens = Vec::new();
-        let mm = state.get_rand_mm();
+        let mm = state.get_rand_mm(rng);
         if mm.is_none() { return (0.0, change_tokens); }  // No major maintenance assigned
 
         let (res, mm_time) = mm.unwrap();
         // Get a second resource
         let other_ass_count = state.assigned_maj_maint().iter().filter(|b| *b).count() - 1;
         if other_ass_count == 0 { return (0.0, change_tokens); }    // No second major maintenance assigned
-        let skip_idx = thread_rng().gen_range(0..other_ass_count);
+        let skip_idx = rng.gen_range(0..other_ass_count);
         let mut other_res = 0;
         let mut counter = 0;
         for (i, _) in state.assigned_maj_maint().iter().enumerate().filter(|(_, b)| *b) {
@@ -80,15 +80,15 @@ impl NeighborhoodFunction for SwapMM {
         
         // Repair a task that was uncovered due to move
         if self.repair {
-            change_tokens.append(&mut state.repair());
+            change_tokens.append(&mut state.repair(rng));
         }
 
         ((state.working_obj_val() as isize - obj_prev as isize) as f64, change_tokens)
     }
 }
 
-impl ToString for SwapMM {
-    fn to_string(&self) -> String {
-        format!("Swap Major ({})", if self.repair { "rep"} else { "norep" })
+impl fmt::Display for SwapMM {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Swap Major ({})", if self.repair { "rep"} else { "norep" })
     }
 }
\ No newline at end of file
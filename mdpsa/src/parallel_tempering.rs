@@ -0,0 +1,183 @@
+use crossbeam_utils::thread;
+use rand::{rngs::StdRng, Rng};
+use std::{error, f64::consts::E, fmt, mem, panic::{self, AssertUnwindSafe}, sync::{atomic::{AtomicUsize, Ordering}, Barrier, Mutex}};
+
+use super::{neighborhood::PenaltyWeights, simulated_annealing::{SimulatedAnnealing, Termination}, state::State};
+
+
+/// Parameters of the replica exchange, the temperatures range from the final to the initial temperature of the annealing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PTParameters {
+    pub replicas: usize,
+    pub exchange_interval: usize   // Iterations of each replica between two exchanges
+}
+
+/// Reason why the replicas cannot be run
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemperingError {
+    NoReplicas,
+    NoExchangeInterval
+}
+
+/// Parallel tempering: replicas at fixed temperatures run in parallel and exchange states between rounds
+pub struct ParallelTempering {
+    parameters: PTParameters,
+    temperatures: Vec<f64>,     // Ascending, replica i runs at temperature i
+    replicas: Vec<SimulatedAnnealing>,
+    rng: StdRng,
+    exchanges: usize,
+    best_feasible: Option<(usize, State)>
+}
+
+impl ParallelTempering {
+    // The temperature ladder is geometric between the final and initial temperature of the first replica
+    pub fn new(replicas: Vec<SimulatedAnnealing>, parameters: PTParameters, rng: StdRng) -> Result<Self, TemperingError> {
+        if replicas.is_empty() {
+            return Err(TemperingError::NoReplicas);
+        }
+        if parameters.exchange_interval == 0 {
+            return Err(TemperingError::NoExchangeInterval);
+        }
+        let coldest = replicas[0].parameters().final_temperature();
+        let hottest = replicas[0].parameters().initial_temperature();
+        let temperatures = (0..replicas.len()).map(|i| {
+            let fraction = if replicas.len() > 1 { i as f64 / (replicas.len() - 1) as f64 } else { 0.0 };
+            coldest * (hottest / coldest).powf(fraction)
+        }).collect();
+
+        let mut pt = ParallelTempering { parameters, temperatures, replicas: Vec::new(), rng, exchanges: 0, best_feasible: None };
+        pt.update_best(replicas.iter());
+        pt.replicas = replicas;
+        Ok(pt)
+    }
+
+    pub fn replicas(&self) -> &Vec<SimulatedAnnealing> {
+        &self.replicas
+    }
+
+    pub fn temperatures(&self) -> &Vec<f64> {
+        &self.temperatures
+    }
+
+    /// Number of accepted state exchanges
+    pub fn exchanges(&self) -> usize {
+        self.exchanges
+    }
+
+    pub fn get_best(&self) -> &Option<(usize, State)> {
+        &self.best_feasible
+    }
+
+    /// Runs rounds of parallel replicas until the termination, returns the iterations per replica. Every replica runs
+    /// in its own thread for the whole search, the rounds are synchronized by barriers. A panic of a replica ends the
+    /// search after its round and is passed on to the caller.
+    pub fn solve(&mut self, termination: Termination) -> usize {
+        let replicas = mem::take(&mut self.replicas).into_iter().map(Mutex::new).collect::<Vec<_>>();
+        let temperatures = self.temperatures.clone();
+        let round_iterations = AtomicUsize::new(0);     // 0 ends the search
+        let round_start = Barrier::new(replicas.len() + 1);
+        let round_end = Barrier::new(replicas.len() + 1);
+        let failure = Mutex::new(None);     // Payload of the first panicking replica
+        let mut iterations = 0;
+        thread::scope(|s| {
+            for (replica, temperature) in replicas.iter().zip(temperatures.iter()) {
+                let (round_iterations, round_start, round_end, failure) = (&round_iterations, &round_start, &round_end, &failure);
+                s.spawn(move |_| loop {
+                    round_start.wait();
+                    let round_iterations = round_iterations.load(Ordering::SeqCst);
+                    if round_iterations == 0 { break; }
+                    // The barriers wait for every thread, a panicking replica still has to finish the round
+                    let run = panic::catch_unwind(AssertUnwindSafe(|| replica.lock().unwrap().run_at(*temperature, round_iterations)));
+                    if let Err(payload) = run {
+                        failure.lock().unwrap().get_or_insert(payload);
+                    }
+                    round_end.wait();
+                });
+            }
+
+            let mut round = 0;
+            loop {
+                let next = match termination {
+                    _ if termination.is_reached(iterations) => 0,
+                    Termination::Iterations(max_iterations) => self.parameters.exchange_interval.min(max_iterations - iterations),
+                    Termination::Deadline(_) => self.parameters.exchange_interval
+                };
+                round_iterations.store(next, Ordering::SeqCst);
+                round_start.wait();
+                if next == 0 { break; }
+                round_end.wait();
+                iterations += next;
+                if failure.lock().unwrap().is_some() {
+                    round_iterations.store(0, Ordering::SeqCst);
+                    round_start.wait();
+                    break;
+                }
+
+                let mut guards = replicas.iter().map(|replica| replica.lock().unwrap()).collect::<Vec<_>>();
+                let mut round_replicas = guards.iter_mut().map(|guard| &mut **guard).collect::<Vec<&mut SimulatedAnnealing>>();
+                self.update_best(round_replicas.iter().map(|replica| &**replica));
+                self.exchange(&mut round_replicas, round % 2);
+                round += 1;
+            }
+        }).unwrap();
+        if let Some(payload) = failure.into_inner().unwrap() {
+            panic::resume_unwind(payload);
+        }
+        self.replicas = replicas.into_iter().map(|replica| replica.into_inner().unwrap()).collect();
+
+        iterations
+    }
+
+    // Metropolis criterion for swapping the states of neighboring replicas (pairs starting at offset). The replicas adapt
+    // their penalty weights independently, the states are compared with the default weights.
+    fn exchange(&mut self, replicas: &mut [&mut SimulatedAnnealing], offset: usize) {
+        let energy = |replica: &SimulatedAnnealing| {
+            let state = replica.neighborhood().state();
+            (state.obj_value() + state.penalty_with(&PenaltyWeights::default())) as f64
+        };
+        for i in (offset..replicas.len().saturating_sub(1)).step_by(2) {
+            let cold = energy(replicas[i]);
+            let hot = energy(replicas[i+1]);
+            let exponent = (1.0 / self.temperatures[i] - 1.0 / self.temperatures[i+1]) * (cold - hot);
+            if exponent >= 0.0 || self.rng.gen::<f64>() < E.powf(exponent) {
+                let cold_state = replicas[i].neighborhood().state().clone();
+                let hot_state = replicas[i+1].neighborhood().state().clone();
+                replicas[i].set_state(hot_state);
+                replicas[i+1].set_state(cold_state);
+                self.exchanges += 1;
+            }
+        }
+    }
+
+    // Global best over all replicas
+    fn update_best<'a>(&mut self, replicas: impl Iterator<Item = &'a SimulatedAnnealing>) {
+        for replica in replicas {
+            if let Some((obj, state)) = replica.get_best() {
+                let add = match self.best_feasible {
+                    Some((prev_best, _)) => *obj < prev_best,
+                    None => true
+                };
+                if add {
+                    self.best_feasible = Some((*obj, state.clone()));
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for TemperingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemperingError::NoReplicas => write!(f, "parallel tempering needs at least one replica"),
+            TemperingError::NoExchangeInterval => write!(f, "the exchange interval must be at least one iteration")
+        }
+    }
+}
+
+impl error::Error for TemperingError {}
+
+impl Default for PTParameters {
+    fn default() -> Self {
+        PTParameters { replicas: 8, exchange_interval: 1000 }
+    }
+}
@@ -0,0 +1,28 @@
+/// Restart of the annealing: where the search continues from and when it is triggered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Restart {
+    pub strategy: RestartStrategy,
+    pub trigger: RestartTrigger
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartStrategy {
+    Best,                           // Best feasible state so far
+    PerturbedBest { moves: usize }, // Best feasible state after a number of random moves
+    Fresh                           // New random initial state
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartTrigger {
+    Stagnation(usize),  // Iterations without improvement (and since the last restart)
+    Interval(usize)     // Every n iterations
+}
+
+impl RestartTrigger {
+    pub fn is_triggered(&self, iterations: usize, iterations_since_improvement: usize, last_restart: usize) -> bool {
+        match *self {
+            RestartTrigger::Stagnation(after) => iterations_since_improvement >= after && iterations - last_restart >= after,
+            RestartTrigger::Interval(interval) => iterations - last_restart >= interval
+        }
+    }
+}
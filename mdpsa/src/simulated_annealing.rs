@@ -1,125 +1,486 @@
-use rand::Rng;
-use super::neighborhood::Neighborhood;
+use super::{acceptance::{Acceptance, AcceptanceCriterion}, adaptive_weights::MoveOutcome, incumbent::Incumbent, cooling::{Cooling, CoolingSchedule, Reheat}, neighborhood::{Neighborhood, PenaltyWeights}, restart::{Restart, RestartStrategy}};
 
 use super::state::State;
-use std::f64::consts::E;
+use std::{cmp, error, f64::consts::E, fmt, sync::Arc, time::{Duration, Instant}};
 
+/// When to stop the annealing, the temperature is cooled by the fraction of the budget used up
+#[derive(Debug, Clone, Copy)]
+pub enum Termination {
+    Iterations(usize),
+    Deadline(Instant)
+}
+
+impl Termination {
+    /// Whether the budget is used up after the given number of iterations
+    pub fn is_reached(&self, iterations: usize) -> bool {
+        match *self {
+            Termination::Iterations(max_iterations) => iterations >= max_iterations,
+            Termination::Deadline(deadline) => Instant::now() >= deadline
+        }
+    }
+
+    /// Clock of time-based cooling: the wall-clock time, or under an iteration budget the used share of `clock` (the
+    /// time the budget stands for), so that seeded runs stay reproducible
+    pub fn schedule_time(&self, progress: f64, elapsed: Duration, clock: Duration) -> Duration {
+        match self {
+            Termination::Iterations(_) => clock.mul_f64(progress),
+            Termination::Deadline(_) => elapsed
+        }
+    }
+}
+
+/// Simulated annealing over a `Neighborhood`, keeps track of the best feasible state
 pub struct SimulatedAnnealing {
     parameters: SAParameters,
     temperature: f64,
+    progress: f64,  // Progress of the schedule, for the acceptance criterion
+    schedule: Box<dyn CoolingSchedule>,
+    acceptance: Box<dyn AcceptanceCriterion>,
+    reheat_origin: (f64, f64),  // (progress, schedule progress) at the last reheat
+    time_shift: Duration,       // Time undone by reheats for time-based schedules
+    penalty_adaption: PenaltyAdaption,
+    restarts: usize,
+    iterations: usize,
+    iterations_since_accept: usize,
+    iterations_since_improvement: usize,
+    verifications: usize,   // State checks of the verification mode
+    best_obj: usize,    // Best working objective (including penalties)
     neighborhood: Neighborhood,
-    best_feasible: Option<(usize, State)>
+    best_feasible: Option<(usize, State)>,  // Kept across restarts
+    incumbent: Option<Arc<Incumbent>>,      // Shared with cooperating runs
+    stop_at: Option<Instant>    // Ends a run under an iteration budget early
+}
+
+// Strategic oscillation: the weight of a penalty type is raised if it was violated throughout the last interval
+// and lowered if it was never violated
+struct PenaltyAdaption {
+    weights: PenaltyWeights,
+    violated: [usize; 3],   // Iterations with unassigned tasks, unassigned maj maintenances, uncovered tasks
+    iterations: usize
 }
 
 impl SimulatedAnnealing {
-    pub fn new(neighborhood: Neighborhood, parameters: SAParameters) -> Self {
+    pub fn new(mut neighborhood: Neighborhood, mut parameters: SAParameters) -> Self {
+        if let Some(calibration) = parameters.calibration() {
+            parameters.calibrate(&neighborhood.sample_deltas(calibration.samples));
+        }
         let temperature = parameters.initial_temperature();
+        let schedule = parameters.cooling().build();
+        let acceptance = parameters.acceptance().build();
+        let state = neighborhood.state();
+        let penalty_adaption = PenaltyAdaption::new(*state.penalty_weights());
+        let best_feasible = state.initial_best();
 
         SimulatedAnnealing {
             parameters,
             temperature,
+            progress: 0.0,
+            schedule,
+            acceptance,
+            reheat_origin: (0.0, 0.0),
+            time_shift: Duration::ZERO,
+            penalty_adaption,
+            restarts: 0,
+            iterations: 0,
+            iterations_since_accept: 0,
+            iterations_since_improvement: 0,
+            verifications: 0,
+            best_obj: usize::MAX,
             neighborhood,
-            best_feasible: None
+            best_feasible,
+            incumbent: None,
+            stop_at: None
         }
     }
     
-    pub fn set_iterations(&mut self, iterations: usize) {
-        self.parameters.set_alpha_to_iterations(iterations);
-    }
-    
     pub fn neighborhood(&self) -> &Neighborhood {
         &self.neighborhood
     }
 
+    // E.g. to perturb the state between two runs
+    pub fn neighborhood_mut(&mut self) -> &mut Neighborhood {
+        &mut self.neighborhood
+    }
+
+    /// Parameters of the run, with the calibrated temperatures if calibration is enabled
+    pub fn parameters(&self) -> &SAParameters {
+        &self.parameters
+    }
+
+    /// Number of restarts of the last `solve`
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// Ends `solve` at the deadline even if the iteration budget is not used up (the cooling still follows the budget)
+    pub fn stop_at(&mut self, deadline: Instant) {
+        self.stop_at = Some(deadline);
+    }
+
+    /// Cooperate with other runs: new bests are published to the incumbent and restarts continue from it
+    pub fn share_incumbent(&mut self, incumbent: Arc<Incumbent>) {
+        if let Some((obj, state)) = &self.best_feasible {
+            incumbent.offer(*obj, state);
+        }
+        self.incumbent = Some(incumbent);
+    }
+
     pub fn get_best(&self) -> &Option<(usize, State)> {
         &self.best_feasible
     }
 
     pub fn reset(&mut self) {
         self.temperature = self.parameters.initial_temperature();
+        self.progress = 0.0;
+        self.acceptance = self.parameters.acceptance().build();
+        self.reheat_origin = (0.0, 0.0);
+        self.time_shift = Duration::ZERO;
+        self.penalty_adaption = PenaltyAdaption::new(*self.neighborhood.state().penalty_weights());
+        self.restarts = 0;
+        self.iterations = 0;
+        self.iterations_since_accept = 0;
+        self.iterations_since_improvement = 0;
+        self.verifications = 0;
+        self.best_obj = usize::MAX;
     }
-    
-    fn decrease_temperature(&mut self) {
-        self.temperature *= self.parameters.alpha()
-    }
-
-    fn accept(&self, delta: f64) -> bool {
-        if delta <= 0f64 {
-            return true;
-        }
-        // high delta = bad move = x small
-        let mut rng = rand::thread_rng();
-        if rng.gen::<f64>() < E.powf(- delta / self.temperature)  {
-            return true;
-        }
-        false
-    }
-
-    pub fn solve(&mut self) -> (usize, usize, usize) {
-        let mut iterations = 0;
-        let mut iterations_since_accept = 0;
-        let mut iterations_since_improvement = 0;
-        let mut best_obj = usize::MAX;
-        while self.temperature > self.parameters.final_temperature() {
-            let delta = self.neighborhood.get_next();
-            let working_penalty = self.neighborhood.state().working_obj_val() - self.neighborhood.state().obj_value();
-            let actual_penalty = self.neighborhood.state().calc_penalty_from_scratch();
-            assert_eq!(working_penalty, actual_penalty);
-            assert!(self.neighborhood.state().is_feasible(false));
-            iterations_since_improvement += 1;
-            iterations += 1;
-            if self.accept(delta) {
-                self.neighborhood.accept();
-                if delta != 0.0 {
-                    iterations_since_accept = 0;
-                }
-                let obj_val = self.neighborhood.state().working_obj_val();
-                if obj_val < best_obj {
-                    best_obj = obj_val;
-                    iterations_since_improvement = 0;
+
+    /// Continue from another state (e.g. exchanged with another replica)
+    pub fn set_state(&mut self, state: State) {
+        self.neighborhood.set_state(state);
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn iterations_since_accept(&self) -> usize {
+        self.iterations_since_accept
+    }
+
+    pub fn iterations_since_improvement(&self) -> usize {
+        self.iterations_since_improvement
+    }
+
+    pub fn verifications(&self) -> usize {
+        self.verifications
+    }
+
+    // Continue from another state with the cooling started over for the rest of the run
+    fn restart(&mut self, restart: Restart, progress: f64, elapsed: Duration) {
+        // Cooperating runs continue from the best solution of all runs, the own best stays the result of the run
+        let shared = self.incumbent.as_ref().and_then(|incumbent| incumbent.get());
+        let best = match (&self.best_feasible, shared) {
+            (Some((own, state)), Some((obj, _))) if *own <= obj => Some(state.clone()),
+            (_, Some((_, state))) => Some(state),
+            (own, None) => own.as_ref().map(|(_, state)| state.clone())
+        };
+        match (restart.strategy, best) {
+            (RestartStrategy::Best, Some(best)) => self.neighborhood.set_state(best),
+            (RestartStrategy::PerturbedBest { moves }, Some(best)) => {
+                self.neighborhood.set_state(best);
+                self.neighborhood.perturb(moves);
+            },
+            // Also if there is no feasible state yet
+            _ => self.neighborhood.reinitialize()
+        }
+        self.reheat(progress, elapsed, 1.0);
+        self.acceptance.reset(self.neighborhood.state().working_obj_val() as f64);
+        self.restarts += 1;
+    }
+
+    // Progress of the schedule, after a reheat it is stretched to still reach 1 at the end of the run
+    fn schedule_progress(&self, progress: f64) -> f64 {
+        let (origin, schedule_origin) = self.reheat_origin;
+        (schedule_origin + (progress - origin) * (1.0 - schedule_origin) / (1.0 - origin)).min(1.0)
+    }
+
+    fn update_temperature(&mut self, progress: f64, elapsed: Duration) {
+        let schedule_progress = self.schedule_progress(progress);
+        self.temperature = self.schedule.temperature(self.parameters.initial_temperature(), self.parameters.final_temperature(),
+            schedule_progress, elapsed.saturating_sub(self.time_shift));
+    }
+
+    // Undo a fraction of the cooling done so far
+    fn reheat(&mut self, progress: f64, elapsed: Duration, fraction: f64) {
+        let schedule_progress = self.schedule_progress(progress) * (1.0 - fraction);
+        self.reheat_origin = (progress, schedule_progress);
+        self.time_shift += elapsed.saturating_sub(self.time_shift).mul_f64(fraction);
+    }
+
+    // Working objectives under old penalty weights are not comparable anymore, the best one is taken from the states known
+    // under the new weights: the best feasible state (no penalty) and the current one
+    fn rescale_best(&mut self) {
+        let current = self.neighborhood.state().working_obj_val();
+        self.best_obj = self.best_feasible.as_ref().map_or(current, |(obj, _)| cmp::min(*obj, current));
+    }
+
+    // Checks the incrementally updated state against a recomputation (O(n), verification mode only)
+    fn verify_state(&mut self) {
+        self.verifications += 1;
+        let state = self.neighborhood.state();
+        let working_penalty = state.working_obj_val() - state.obj_value();
+        let actual_penalty = state.calc_penalty_from_scratch();
+        assert_eq!(working_penalty, actual_penalty, "Incremental penalty differs from the recomputed penalty");
+        assert!(state.is_feasible(false), "Inconsistent state after {} iterations", self.iterations);
+    }
+
+    fn accept(&mut self, delta: f64) -> bool {
+        let candidate = self.neighborhood.state().working_obj_val() as f64;
+        self.acceptance.accept(delta, candidate, self.temperature, self.neighborhood.rng())
+    }
+
+    pub fn solve(&mut self, termination: Termination) -> (usize, usize, usize) {
+        let mut last_reheat = 0;
+        let mut last_restart = 0;
+        let start = Instant::now();
+        self.reset();
+        loop {
+            if termination.is_reached(self.iterations) { break; }
+            let now = Instant::now();
+            if self.stop_at.is_some_and(|deadline| now >= deadline) { break; }
+            let progress = match termination {
+                Termination::Iterations(max_iterations) => self.iterations as f64 / max_iterations as f64,
+                Termination::Deadline(deadline) => (now.duration_since(start).as_secs_f64() / deadline.duration_since(start).as_secs_f64()).min(1.0)
+            };
+            let elapsed = termination.schedule_time(progress, now.duration_since(start), self.parameters.iteration_clock());
+            if let Some(reheat) = self.parameters.reheat() {
+                if self.iterations_since_improvement >= reheat.after && self.iterations - last_reheat >= reheat.after {
+                    self.reheat(progress, elapsed, reheat.fraction);
+                    last_reheat = self.iterations;
                 }
-                let state = self.neighborhood().state();
-                if state.is_feasible_quick() {
-                    let obj = state.obj_value();
-                    // Try update, but ensure feasibility
-                    let add = match self.best_feasible {
-                        Some((prev_best, _)) => obj < prev_best,
-                        None => true
-                    };
-                    if add && state.is_feasible(true) {
-                        self.best_feasible = Some((obj, state.clone()));
-                    }
+            }
+            if let Some(restart) = self.parameters.restart() {
+                if restart.trigger.is_triggered(self.iterations, self.iterations_since_improvement, last_restart) {
+                    self.restart(restart, progress, elapsed);
+                    last_restart = self.iterations;
                 }
+            }
+            self.progress = self.schedule_progress(progress);
+            self.update_temperature(progress, elapsed);
+            self.step();
+        }
+
+        (self.iterations, self.iterations_since_accept, self.iterations_since_improvement)
+    }
+
+    /// Runs a number of iterations at a fixed temperature (e.g. as replica of parallel tempering)
+    pub fn run_at(&mut self, temperature: f64, iterations: usize) {
+        self.temperature = temperature;
+        for _ in 0..iterations {
+            self.step();
+        }
+    }
+
+    // A single move: accept or roll back, track the best states and adapt the penalty weights
+    fn step(&mut self) {
+        let delta = self.neighborhood.get_next();
+        if self.parameters.verify_every() != 0 && self.iterations.is_multiple_of(self.parameters.verify_every()) {
+            self.verify_state();
+        }
+        self.iterations_since_improvement += 1;
+        self.iterations += 1;
+        if self.accept(delta) {
+            if delta != 0.0 {
+                self.iterations_since_accept = 0;
+            }
+            let obj_val = self.neighborhood.state().working_obj_val();
+            let outcome = if obj_val < self.best_obj {
+                MoveOutcome::NewBest
+            } else if delta < 0.0 {
+                MoveOutcome::Improved
             } else {
-                self.neighborhood.reject();
-                iterations_since_accept += 1;
+                MoveOutcome::Accepted
+            };
+            self.neighborhood.accept(outcome);
+            if obj_val < self.best_obj {
+                self.best_obj = obj_val;
+                self.iterations_since_improvement = 0;
+            }
+            if self.neighborhood.state().update_best(&mut self.best_feasible) {
+                if let (Some(incumbent), Some((obj, state))) = (&self.incumbent, &self.best_feasible) {
+                    incumbent.offer(*obj, state);
+                }
             }
+        } else {
+            self.neighborhood.reject();
+            self.iterations_since_accept += 1;
+        }
+
+        self.acceptance.update(self.neighborhood.state().working_obj_val() as f64, self.progress);
 
-            self.decrease_temperature();
-            // if iterations % (self.parameters.iterations() / self.parameters.max_penalty) == 0 {
-            //     self.neighborhood.increase_penalty_multi();
-            // }
+        if let Some(interval) = self.parameters.penalty_interval() {
+            if let Some(weights) = self.penalty_adaption.record(self.neighborhood.state(), interval, self.parameters.max_penalty()) {
+                self.neighborhood.set_penalty_weights(weights);
+                self.rescale_best();
+                self.acceptance.reset(self.neighborhood.state().working_obj_val() as f64);
+            }
         }
+    }
+}
 
-        (iterations, iterations_since_accept, iterations_since_improvement)
+impl PenaltyAdaption {
+    fn new(weights: PenaltyWeights) -> Self {
+        PenaltyAdaption { weights, violated: [0; 3], iterations: 0 }
+    }
+
+    // Returns the new weights at the end of an interval if they changed
+    fn record(&mut self, state: &State, interval: usize, max_penalty: usize) -> Option<PenaltyWeights> {
+        if !state.is_feasible_quick() {
+            self.violated[0] += state.has_unassigned_tasks() as usize;
+            self.violated[1] += state.has_unassigned_maj_maints() as usize;
+            self.violated[2] += state.has_uncovered_tasks() as usize;
+        }
+        self.iterations += 1;
+        if self.iterations < interval {
+            return None;
+        }
+
+        // Weights stay in [1, max_penalty], a weight of 0 would make infeasible states look feasible
+        let adapt = |weight: usize, violated: usize| match violated {
+            0 => weight.saturating_sub(1),
+            v if v == interval => cmp::min(weight + 1, max_penalty),
+            _ => weight
+        }.max(1);
+        let weights = PenaltyWeights {
+            task: adapt(self.weights.task, self.violated[0]),
+            maj_maint: adapt(self.weights.maj_maint, self.violated[1]),
+            uncovered: adapt(self.weights.uncovered, self.violated[2])
+        };
+        self.violated = [0; 3];
+        self.iterations = 0;
+        if weights == self.weights {
+            return None;
+        }
+        self.weights = weights;
+
+        Some(weights)
     }
 }
 
+/// Parameters of the annealing schedule
+#[derive(Debug, Clone)]
 pub struct SAParameters {
-    alpha: f64,
     initial_temperature: f64,
     final_temperature: f64,
     max_penalty: usize,
-    iterations: usize
+    cooling: Cooling,
+    reheat: Option<Reheat>,
+    calibration: Option<Calibration>,
+    acceptance: Acceptance,
+    restart: Option<Restart>,
+    penalty_interval: Option<usize>,    // Adapt the penalty weights (up to max_penalty) every n iterations
+    iteration_clock: Duration,  // Time an iteration budget stands for on the clock of time-based cooling
+    verify_every: usize    // Verify the state every n iterations (0 = never)
+}
+
+/// Reason why annealing parameters cannot be used
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterError {
+    InvalidTemperatures { initial_temperature: f64, final_temperature: f64 }    // Not finite with initial > final > 0
 }
 
 impl SAParameters {
-    pub fn new(initial_temperature: f64, final_temperature: f64, max_penalty: usize, iterations: usize) -> Self {
-        SAParameters { alpha: 0.99, initial_temperature, final_temperature, max_penalty, iterations }
+    /// The temperatures must be finite with `initial_temperature > final_temperature > 0`, `max_penalty` is at least 1,
+    /// lower values are raised to 1
+    pub fn new(initial_temperature: f64, final_temperature: f64, max_penalty: usize) -> Result<Self, ParameterError> {
+        if !valid_temperatures(initial_temperature, final_temperature) {
+            return Err(ParameterError::InvalidTemperatures { initial_temperature, final_temperature });
+        }
+
+        Ok(SAParameters { initial_temperature, final_temperature, max_penalty: max_penalty.max(1), ..SAParameters::default() })
+    }
+
+    pub fn with_cooling(mut self, cooling: Cooling) -> Self {
+        self.cooling = cooling;
+        self
+    }
+
+    /// Reheat when the best state has not improved for a number of iterations
+    pub fn with_reheat(mut self, reheat: Reheat) -> Self {
+        self.reheat = Some(reheat);
+        self
+    }
+
+    /// Use another acceptance criterion than Metropolis (the temperature is the threshold of threshold accepting)
+    pub fn with_acceptance(mut self, acceptance: Acceptance) -> Self {
+        self.acceptance = acceptance;
+        self
+    }
+
+    pub fn with_restart(mut self, restart: Restart) -> Self {
+        self.restart = Some(restart);
+        self
+    }
+
+    /// Adapt the penalty weights every `interval` iterations to the infeasibilities of the search
+    pub fn with_adaptive_penalty(mut self, interval: usize) -> Self {
+        self.penalty_interval = Some(interval);
+        self
+    }
+
+    /// Derive the temperatures from sampled moves of the initial state instead of using fixed values
+    pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    // Temperatures at which the sampled worsening moves are accepted with the target probabilities on average
+    fn calibrate(&mut self, deltas: &[f64]) {
+        let calibration = self.calibration.unwrap();
+        let worsening = deltas.iter().copied().filter(|delta| *delta > 0.0).collect::<Vec<f64>>();
+        if worsening.is_empty() {
+            return;
+        }
+        let initial_temperature = temperature_for_acceptance(&worsening, calibration.initial_acceptance);
+        let final_temperature = temperature_for_acceptance(&worsening, calibration.final_acceptance);
+        // Both bounds of the bisection reached, the fixed temperatures stay
+        if valid_temperatures(initial_temperature, final_temperature) {
+            self.initial_temperature = initial_temperature;
+            self.final_temperature = final_temperature;
+        }
+    }
+
+    /// Spread an iteration budget over this time for time-based cooling (exponential) instead of the wall-clock time
+    pub fn with_iteration_clock(mut self, clock: Duration) -> Self {
+        self.iteration_clock = clock;
+        self
+    }
+
+    pub fn iteration_clock(&self) -> Duration {
+        self.iteration_clock
+    }
+
+    pub fn with_verify_every(mut self, verify_every: usize) -> Self {
+        self.verify_every = verify_every;
+        self
+    }
+
+    pub fn verify_every(&self) -> usize {
+        self.verify_every
+    }
+
+    pub fn cooling(&self) -> Cooling {
+        self.cooling
+    }
+
+    pub fn reheat(&self) -> Option<Reheat> {
+        self.reheat
+    }
+
+    pub fn calibration(&self) -> Option<Calibration> {
+        self.calibration
+    }
+
+    pub fn acceptance(&self) -> Acceptance {
+        self.acceptance
+    }
+
+    pub fn restart(&self) -> Option<Restart> {
+        self.restart
     }
 
-    pub fn alpha(&self)-> f64 {
-        self.alpha
+    pub fn penalty_interval(&self) -> Option<usize> {
+        self.penalty_interval
     }
 
     pub fn max_penalty(&self)-> usize {
@@ -133,20 +494,86 @@ impl SAParameters {
     pub fn final_temperature(&self) -> f64 {
         self.final_temperature
     }
-    pub fn set_alpha_to_iterations(&mut self, iterations: usize) {
-        self.iterations = iterations;
-        self.alpha = (self.final_temperature / self.initial_temperature).powf(1.0 / iterations as f64)
-    }
 }
 
 impl Default for SAParameters {
     fn default() -> Self {
         SAParameters {
-            alpha: 0.99,
             initial_temperature: 10000.0,
             final_temperature: 10.0,
             max_penalty: 10,
-            iterations: 100000
+            cooling: Cooling::Geometric,
+            reheat: None,
+            calibration: None,
+            acceptance: Acceptance::Metropolis,
+            restart: None,
+            penalty_interval: None,
+            iteration_clock: Duration::from_secs(600),
+            // Debug builds (and tests) check every iteration, release builds run at full speed
+            verify_every: if cfg!(debug_assertions) { 1 } else { 0 }
+        }
+    }
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterError::InvalidTemperatures { initial_temperature, final_temperature } =>
+                write!(f, "the temperatures must be finite with initial > final > 0 (initial {}, final {})", initial_temperature, final_temperature)
+        }
+    }
+}
+
+impl error::Error for ParameterError {}
+
+/// Target acceptance probabilities of worsening moves at the start and the end of the run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub samples: usize,
+    pub initial_acceptance: f64,
+    pub final_acceptance: f64
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Calibration { samples: 1000, initial_acceptance: 0.5, final_acceptance: 0.001 }
+    }
+}
+
+fn valid_temperatures(initial_temperature: f64, final_temperature: f64) -> bool {
+    initial_temperature.is_finite() && final_temperature > 0.0 && initial_temperature > final_temperature
+}
+
+// Bisection (on a log scale) for the temperature T with mean(e^(-delta/T)) = acceptance
+fn temperature_for_acceptance(deltas: &[f64], acceptance: f64) -> f64 {
+    let mean_acceptance = |temperature: f64| deltas.iter().map(|delta| E.powf(-delta / temperature)).sum::<f64>() / deltas.len() as f64;
+    let (mut low, mut high) = (1e-6f64.ln(), 1e12f64.ln());
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if mean_acceptance(mid.exp()) < acceptance {
+            low = mid;
+        } else {
+            high = mid;
         }
     }
-}
\ No newline at end of file
+
+    ((low + high) / 2.0).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_decreasing_temperatures() {
+        assert!(SAParameters::new(100.0, 1.0, 0).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_temperatures() {
+        for (initial_temperature, final_temperature) in [(10.0, 10.0), (10.0, 20.0), (10.0, 0.0), (0.0, -1.0), (f64::NAN, 1.0), (10.0, f64::NAN), (f64::INFINITY, 1.0)] {
+            let err = SAParameters::new(initial_temperature, final_temperature, 1).unwrap_err();
+            assert!(matches!(err, ParameterError::InvalidTemperatures { .. }), "{} {}", initial_temperature, final_temperature);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::state::{JobToken, State};
+
+
+/// Serializable schedule of a solution (all times are end times)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Solution {
+    objective: usize,
+    penalty: usize,
+    resources: Vec<ResourceSchedule>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResourceSchedule {
+    major_maintenance_end: Option<usize>,
+    regular_maintenance_ends: Vec<usize>,
+    tasks: Vec<usize>   // Task ids ordered by time
+}
+
+impl Solution {
+    pub fn from_state(state: &State) -> Self {
+        let resources = state.jobs().iter().map(|jobs| {
+            let mut schedule = ResourceSchedule { major_maintenance_end: None, regular_maintenance_ends: Vec::new(), tasks: Vec::new() };
+            for (time, job) in jobs.iter() {
+                match job {
+                    JobToken::MajMaint => schedule.major_maintenance_end = Some(*time),
+                    JobToken::RegMaint => schedule.regular_maintenance_ends.push(*time),
+                    JobToken::Task(id) => schedule.tasks.push(*id)
+                }
+            }
+            schedule
+        }).collect();
+
+        Solution {
+            objective: state.obj_value(),
+            penalty: state.penalty_value(),
+            resources
+        }
+    }
+
+    pub fn new_from_file(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn objective(&self) -> usize {
+        self.objective
+    }
+
+    pub fn resources(&self) -> &Vec<ResourceSchedule> {
+        &self.resources
+    }
+}
+
+impl ResourceSchedule {
+    pub fn major_maintenance_end(&self) -> Option<usize> {
+        self.major_maintenance_end
+    }
+
+    pub fn regular_maintenance_ends(&self) -> &Vec<usize> {
+        &self.regular_maintenance_ends
+    }
+
+    pub fn tasks(&self) -> &Vec<usize> {
+        &self.tasks
+    }
+}
@@ -0,0 +1,388 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{alns::{ALNSParameters, AdaptiveLargeNeighborhoodSearch}, branch_and_bound::{BBParameters, BranchAndBound}, incumbent::Incumbent, instance::Instance, iterated_local_search::{ILSParameters, IteratedLocalSearch}, memetic::{GAParameters, MemeticAlgorithm}, neighborhood::{Neighborhood, NeighborhoodConfig, OperatorStats, PenaltyWeights}, parallel_tempering::{PTParameters, ParallelTempering}, simulated_annealing::{SAParameters, SimulatedAnnealing, Termination}, solution::Solution, state::State, tabu_search::{TSParameters, TabuSearch}};
+
+
+/// Configuration of a single solver run.
+#[derive(Debug, Clone)]
+pub struct Config {
+    parameters: SAParameters,
+    neighborhood: NeighborhoodConfig,
+    time_limit: Duration,
+    iterations: Option<usize>,
+    seed: u64,
+    warm_start: Option<Solution>,
+    algorithm: Algorithm,
+    incumbent: Option<Arc<Incumbent>>
+}
+
+/// Outcome of a single solver run.
+#[derive(Debug, Clone)]
+pub struct SolveReport {
+    best: Option<Solution>,
+    seed: u64,
+    iterations: usize,
+    iterations_since_accept: usize,
+    iterations_since_improvement: usize,
+    runtime: Duration,
+    temperatures: (f64, f64),   // (initial, final), calibrated if enabled
+    penalty_weights: PenaltyWeights,
+    restarts: usize,
+    exchanges: usize,   // Accepted exchanges of parallel tempering
+    operator_weights: Vec<(String, f64)>,
+    operator_stats: Vec<OperatorStats>,
+    lower_bound: Option<usize>  // Proven lower bound of the objective (exact solver only)
+}
+
+/// Algorithm of a solver run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    SimulatedAnnealing,
+    ParallelTempering(PTParameters),
+    TabuSearch(TSParameters),
+    AdaptiveLargeNeighborhoodSearch(ALNSParameters),  // Temperatures and acceptance of the annealing parameters
+    Memetic(GAParameters),  // Local search with the annealing parameters
+    IteratedLocalSearch(ILSParameters),
+    BranchAndBound(BBParameters)    // Exact, iterations are nodes, seeded by an annealing run
+}
+
+/// Solves the instance with the configured algorithm and returns the best feasible solution found.
+///
+/// The run stops after `Config::time_limit`, or after a fixed number of iterations if
+/// `Config::with_iterations` is set, in which case it is reproducible from its seed.
+pub fn solve(instance: &Instance, config: &Config) -> SolveReport {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    match config.algorithm {
+        Algorithm::SimulatedAnnealing => {
+            let neighborhood = initial_neighborhood(instance, config, rng);
+            let mut sa = SimulatedAnnealing::new(neighborhood, config.annealing_parameters());
+            if let Some(incumbent) = &config.incumbent {
+                sa.share_incumbent(incumbent.clone());
+            }
+            let timer = Instant::now();
+            sa.solve(config.termination(timer));
+
+            SolveReport::from_annealing(&sa, sa.get_best(), config.seed, timer.elapsed())
+        },
+        Algorithm::ParallelTempering(parameters) => {
+            // Every replica draws from its own generator, derived from the seed
+            let parameters = PTParameters { replicas: parameters.replicas.max(1), exchange_interval: parameters.exchange_interval.max(1) };
+            let replicas = (0..parameters.replicas).map(|_| {
+                let replica_rng = StdRng::seed_from_u64(rng.gen());
+                SimulatedAnnealing::new(initial_neighborhood(instance, config, replica_rng), config.annealing_parameters())
+            }).collect();
+            let mut pt = ParallelTempering::new(replicas, parameters, rng).expect("Tempering parameters are clamped to valid values");
+            let timer = Instant::now();
+            pt.solve(config.termination(timer));
+
+            // Coldest replica, with iterations and operator statistics summed over all replicas
+            let mut report = SolveReport::from_annealing(&pt.replicas()[0], pt.get_best(), config.seed, timer.elapsed());
+            report.iterations = pt.replicas().iter().map(|replica| replica.iterations()).sum();
+            for replica in pt.replicas().iter().skip(1) {
+                for (total, stats) in report.operator_stats.iter_mut().zip(replica.neighborhood().operator_stats().iter()) {
+                    total.merge(stats);
+                }
+            }
+            report.temperatures = (*pt.temperatures().last().unwrap(), pt.temperatures()[0]);
+            report.exchanges = pt.exchanges();
+            report
+        },
+        Algorithm::TabuSearch(parameters) => {
+            let mut ts = TabuSearch::new(initial_neighborhood(instance, config, rng), parameters);
+            let timer = Instant::now();
+            ts.solve(config.termination(timer));
+
+            SolveReport {
+                iterations: ts.iterations(),
+                iterations_since_improvement: ts.iterations_since_improvement(),
+                ..SolveReport::new(ts.neighborhood(), ts.get_best(), config.seed, timer.elapsed())
+            }
+        },
+        Algorithm::AdaptiveLargeNeighborhoodSearch(parameters) => {
+            let state = initial_state(instance, config, &mut rng);
+            let mut alns = AdaptiveLargeNeighborhoodSearch::new(state, rng, parameters, config.annealing_parameters());
+            let timer = Instant::now();
+            alns.solve(config.termination(timer));
+
+            SolveReport {
+                best: alns.get_best().as_ref().map(|(_, state)| Solution::from_state(state)),
+                seed: config.seed,
+                iterations: alns.iterations(),
+                iterations_since_accept: alns.iterations_since_accept(),
+                iterations_since_improvement: alns.iterations_since_improvement(),
+                runtime: timer.elapsed(),
+                temperatures: (config.parameters.initial_temperature(), config.parameters.final_temperature()),
+                penalty_weights: *alns.state().penalty_weights(),
+                restarts: 0,
+                exchanges: 0,
+                operator_weights: alns.operator_weights(),
+                operator_stats: alns.operator_stats().clone(),
+                lower_bound: None
+            }
+        },
+        Algorithm::Memetic(parameters) => {
+            // The first individual is the warm start if given, the local search draws from its own generator
+            let mut population = vec![initial_state(instance, config, &mut rng)];
+            while population.len() < parameters.population {
+                let mut state = State::new(instance.clone(), PenaltyWeights::default());
+                state.initialize(&mut rng);
+                population.push(state);
+            }
+            let local_rng = StdRng::seed_from_u64(rng.gen());
+            let local_search = SimulatedAnnealing::new(Neighborhood::from_state(population[0].clone(), local_rng, &config.neighborhood), config.annealing_parameters());
+            let mut ma = MemeticAlgorithm::new(population, local_search, parameters, rng);
+            let timer = Instant::now();
+            ma.solve(config.termination(timer));
+
+            SolveReport {
+                iterations: ma.iterations(),
+                ..SolveReport::from_annealing(ma.local_search(), ma.get_best(), config.seed, timer.elapsed())
+            }
+        },
+        Algorithm::IteratedLocalSearch(parameters) => {
+            let mut ils = IteratedLocalSearch::new(initial_neighborhood(instance, config, rng), parameters);
+            let timer = Instant::now();
+            ils.solve(config.termination(timer));
+
+            SolveReport {
+                iterations: ils.iterations(),
+                iterations_since_improvement: ils.iterations_since_improvement(),
+                ..SolveReport::new(ils.neighborhood(), ils.get_best(), config.seed, timer.elapsed())
+            }
+        },
+        Algorithm::BranchAndBound(parameters) => {
+            let timer = Instant::now();
+            let termination = config.termination(timer);
+            let mut sa = SimulatedAnnealing::new(initial_neighborhood(instance, config, rng), config.annealing_parameters());
+            // The seed run stays within the budget of the run
+            let seed_iterations = match termination {
+                Termination::Iterations(max_iterations) => parameters.seed_iterations.min(max_iterations),
+                Termination::Deadline(deadline) => {
+                    sa.stop_at(deadline);
+                    parameters.seed_iterations
+                }
+            };
+            if seed_iterations > 0 {
+                sa.solve(Termination::Iterations(seed_iterations));
+            }
+            let mut bb = BranchAndBound::new(instance.clone(), sa.get_best().clone());
+            bb.solve(termination);
+
+            SolveReport {
+                iterations: bb.nodes(),
+                iterations_since_improvement: bb.nodes_since_improvement(),
+                lower_bound: bb.lower_bound(),
+                ..SolveReport::new(sa.neighborhood(), bb.get_best(), config.seed, timer.elapsed())
+            }
+        }
+    }
+}
+
+fn initial_neighborhood(instance: &Instance, config: &Config, mut rng: StdRng) -> Neighborhood {
+    let state = initial_state(instance, config, &mut rng);
+    Neighborhood::from_state(state, rng, &config.neighborhood)
+}
+
+// Random initial state or the repaired warm start
+fn initial_state(instance: &Instance, config: &Config, rng: &mut StdRng) -> State {
+    match &config.warm_start {
+        Some(solution) => {
+            let mut state = State::from_solution(instance.clone(), PenaltyWeights::default(), solution);
+            state.repair(rng);
+            state
+        },
+        None => {
+            let mut state = State::new(instance.clone(), PenaltyWeights::default());
+            state.initialize(rng);
+            state
+        }
+    }
+}
+
+impl Config {
+    pub fn new(parameters: SAParameters, time_limit: Duration, seed: u64) -> Self {
+        Config { parameters, neighborhood: NeighborhoodConfig::default(), time_limit, iterations: None, seed, warm_start: None, algorithm: Algorithm::SimulatedAnnealing, incumbent: None }
+    }
+
+    /// Use a different operator pool than `NeighborhoodConfig::default()`
+    pub fn with_neighborhood(mut self, neighborhood: NeighborhoodConfig) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    /// Run a fixed number of iterations instead of cooling by the time limit
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// Start from a previous solution instead of a random initial state
+    pub fn with_warm_start(mut self, solution: Solution) -> Self {
+        self.warm_start = Some(solution);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Use another algorithm than plain simulated annealing
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Cooperate with all runs sharing this incumbent (simulated annealing only), restarts continue from it
+    pub fn with_incumbent(mut self, incumbent: Arc<Incumbent>) -> Self {
+        self.incumbent = Some(incumbent);
+        self
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    // Time-based cooling spreads an iteration budget over the time limit
+    fn annealing_parameters(&self) -> SAParameters {
+        self.parameters.clone().with_iteration_clock(self.time_limit)
+    }
+
+    fn termination(&self, start: Instant) -> Termination {
+        match self.iterations {
+            Some(iterations) => Termination::Iterations(iterations),
+            None => Termination::Deadline(start + self.time_limit)
+        }
+    }
+
+    pub fn parameters(&self) -> &SAParameters {
+        &self.parameters
+    }
+
+    pub fn neighborhood(&self) -> &NeighborhoodConfig {
+        &self.neighborhood
+    }
+
+    pub fn time_limit(&self) -> Duration {
+        self.time_limit
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new(SAParameters::default(), Duration::from_secs(600), rand::random())
+    }
+}
+
+impl SolveReport {
+    // Algorithm independent part of the report, the rest is filled in by the algorithm
+    fn new(neighborhood: &Neighborhood, best: &Option<(usize, State)>, seed: u64, runtime: Duration) -> Self {
+        SolveReport {
+            best: best.as_ref().map(|(_, state)| Solution::from_state(state)),
+            seed,
+            iterations: 0,
+            iterations_since_accept: 0,
+            iterations_since_improvement: 0,
+            runtime,
+            temperatures: (0.0, 0.0),
+            penalty_weights: *neighborhood.state().penalty_weights(),
+            restarts: 0,
+            exchanges: 0,
+            operator_weights: neighborhood.operator_weights(),
+            operator_stats: neighborhood.operator_stats().clone(),
+            lower_bound: None
+        }
+    }
+
+    fn from_annealing(sa: &SimulatedAnnealing, best: &Option<(usize, State)>, seed: u64, runtime: Duration) -> Self {
+        SolveReport {
+            iterations: sa.iterations(),
+            iterations_since_accept: sa.iterations_since_accept(),
+            iterations_since_improvement: sa.iterations_since_improvement(),
+            temperatures: (sa.parameters().initial_temperature(), sa.parameters().final_temperature()),
+            restarts: sa.restarts(),
+            ..SolveReport::new(sa.neighborhood(), best, seed, runtime)
+        }
+    }
+
+    /// Best feasible solution, `None` if no feasible solution was found
+    pub fn best(&self) -> Option<&Solution> {
+        self.best.as_ref()
+    }
+
+    pub fn obj_val(&self) -> Option<usize> {
+        self.best.as_ref().map(|solution| solution.objective())
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        self.best.is_some()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn iterations_since_accept(&self) -> usize {
+        self.iterations_since_accept
+    }
+
+    pub fn iterations_since_improvement(&self) -> usize {
+        self.iterations_since_improvement
+    }
+
+    pub fn runtime(&self) -> Duration {
+        self.runtime
+    }
+
+    /// Initial and final temperature of the run (after calibration)
+    pub fn temperatures(&self) -> (f64, f64) {
+        self.temperatures
+    }
+
+    /// Penalty weights at the end of the run (adapted if enabled)
+    pub fn penalty_weights(&self) -> &PenaltyWeights {
+        &self.penalty_weights
+    }
+
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    pub fn exchanges(&self) -> usize {
+        self.exchanges
+    }
+
+    /// Selection weight of each operator at the end of the run (learned if the selection is adaptive)
+    pub fn operator_weights(&self) -> &Vec<(String, f64)> {
+        &self.operator_weights
+    }
+
+    /// Calls, accepts, improvements etc. of each operator, in the same order as `operator_weights`
+    pub fn operator_stats(&self) -> &Vec<OperatorStats> {
+        &self.operator_stats
+    }
+
+    /// Proven lower bound of the objective, equal to the objective if the best solution is optimal
+    pub fn lower_bound(&self) -> Option<usize> {
+        self.lower_bound
+    }
+
+    /// Relative gap between the best solution and the lower bound
+    pub fn gap(&self) -> Option<f64> {
+        match (self.obj_val(), self.lower_bound) {
+            (Some(obj_val), Some(lower_bound)) if obj_val > 0 => Some((obj_val - lower_bound) as f64 / obj_val as f64),
+            (Some(_), Some(_)) => Some(0.0),
+            _ => None
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use bit_vec::BitVec;
-use crate::{instance::Instance, neighborhood::{ChangeToken, PenaltyToken}};
+use crate::{instance::Instance, neighborhood::{ChangeToken, PenaltyToken, PenaltyWeights}, solution::Solution};
 use std::{collections::{BTreeMap, BTreeSet}, cmp};
-use rand::prelude::*;
+use rand::{prelude::*, rngs::StdRng};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum JobToken {
@@ -10,6 +10,7 @@ pub enum JobToken {
     MajMaint
 }
 
+/// Incrementally updated (possibly infeasible) schedule, infeasibilities are penalized
 #[derive(Debug, Clone)]
 pub struct State {
     // Currently unassigned jobs:
@@ -23,13 +24,13 @@ pub struct State {
     task_ass: Vec<usize>,   // To which res a task is assigned to
     maintenance_changes: BTreeMap<usize, ChangeTimestamp>, // Number of maintenences after the timestamp
     obj_value: usize,   // Obj value of instance (without penalties)
-    penalty_value: usize,   // Current penalty value (including modifier)
+    penalty_value: usize,   // Current penalty value (weighted)
     uncovered: Vec<BTreeSet<usize>>,  // Uncovered tasks (end time of task), if you need ID -> get via jobs
-    penalty_multi: usize // Current penalty modifier
+    penalty_weights: PenaltyWeights // Current weights of the penalty types
 }
 
 impl State {
-    pub fn new(instance: Instance, initial_penalty: usize) -> Self {
+    pub fn new(instance: Instance, penalty_weights: PenaltyWeights) -> Self {
         let res = instance.resources();
         let assigned_tasks = BitVec::from_elem(instance.tasks().len(), false);
         let assigned_maj_maint = BitVec::from_elem(res, false);
@@ -47,7 +48,7 @@ impl State {
             obj_value: 0, 
             penalty_value: 0,
             uncovered: vec![BTreeSet::new(); res],
-            penalty_multi: initial_penalty
+            penalty_weights
         }
     }
 
@@ -63,6 +64,10 @@ impl State {
         self.obj_value
     }
 
+    pub fn penalty_value(&self) -> usize {
+        self.penalty_value
+    }
+
     pub fn working_obj_val(&self) -> usize {
         self.obj_value + self.penalty_value
     }
@@ -79,23 +84,68 @@ impl State {
         &self.uncovered
     }
 
+    // Resource of each task (usize::MAX if unassigned)
+    pub fn task_ass(&self) -> &Vec<usize> {
+        &self.task_ass
+    }
+
     pub fn maj_maint_ends(&self) -> &Vec<usize> {
         &self.maj_maint_ends
     }
     
-    pub fn penalty_multi(&self) -> usize {
-        self.penalty_multi
+    pub fn penalty_weights(&self) -> &PenaltyWeights {
+        &self.penalty_weights
     }
 
-    pub fn initialize(&mut self) {
-        // Add initial penalties for unassigned stuff
-        for _ in 0..self.instance.resources() {
-            self.penalty_value += PenaltyToken::MajMaint.to_penalty(&self.instance, self.penalty_multi);
+    // Changing the weights requires recomputing the penalty (O(n))
+    pub fn set_penalty_weights(&mut self, penalty_weights: PenaltyWeights) {
+        self.penalty_weights = penalty_weights;
+        self.penalty_value = self.calc_penalty_from_scratch();
+    }
+
+    pub fn has_unassigned_tasks(&self) -> bool {
+        !self.assigned_tasks.all()
+    }
+
+    pub fn has_unassigned_maj_maints(&self) -> bool {
+        !self.assigned_maj_maint.all()
+    }
+
+    pub fn has_uncovered_tasks(&self) -> bool {
+        self.uncovered.iter().any(|uncovered| !uncovered.is_empty())
+    }
+
+    // Builds a state from a (possibly outdated) solution: jobs that do not fit the instance anymore are left unassigned
+    pub fn from_solution(instance: Instance, penalty_weights: PenaltyWeights, solution: &Solution) -> Self {
+        let mut state = State::new(instance, penalty_weights);
+        state.add_initial_penalties();
+        let resources = cmp::min(state.instance.resources(), solution.resources().len());
+        for (res, schedule) in solution.resources().iter().take(resources).enumerate() {
+            if let Some(end_time) = schedule.major_maintenance_end() {
+                if state.can_add_maintenance(res, end_time, true) {
+                    state.add_major_maintenance(res, end_time);
+                }
+            }
         }
-        for task_id in 0..self.instance.tasks().len() {
-            self.penalty_value += PenaltyToken::Task(task_id).to_penalty(&self.instance, self.penalty_multi);
+        for (res, schedule) in solution.resources().iter().take(resources).enumerate() {
+            for &end_time in schedule.regular_maintenance_ends().iter() {
+                if state.can_add_maintenance(res, end_time, false) {
+                    state.add_regular_maintenance(res, end_time);
+                }
+            }
+            for &task_id in schedule.tasks().iter() {
+                if task_id < state.instance.tasks().len() && !state.assigned_tasks[task_id] && state.can_add_task(res, task_id) {
+                    state.add_task(res, task_id);
+                }
+            }
         }
-        self.repair();
+
+        state
+    }
+
+    pub fn initialize(&mut self, rng: &mut StdRng) {
+        self.add_initial_penalties();
+        self.repair(rng);
 
         // DETERMINISTIC GENERATION FOR DEBUGGING
         // // Add major maintenances at random (non-overlapping) times
@@ -144,18 +194,48 @@ impl State {
         // DETERMINISTIC GENERATION FOR DEBUGGING
     }
 
+    /// Penalties of the empty state (all tasks and major maintenances unassigned), due before jobs are added to a new state
+    pub fn add_initial_penalties(&mut self) {
+        for _ in 0..self.instance.resources() {
+            self.penalty_value += PenaltyToken::MajMaint.to_penalty(&self.instance, &self.penalty_weights);
+        }
+        for task_id in 0..self.instance.tasks().len() {
+            self.penalty_value += PenaltyToken::Task(task_id).to_penalty(&self.instance, &self.penalty_weights);
+        }
+    }
+
     pub fn is_feasible_quick(&self) -> bool {
         self.penalty_value == 0
     }
 
-    pub fn increase_penalty_multi(&mut self) {
-        let old_multi = self.penalty_multi;
-        self.penalty_multi += 1;
-        self.penalty_value /= old_multi;
-        self.penalty_value *= self.penalty_multi;
+    /// Best feasible state of a search that starts here, the start point might already be feasible (e.g. warm start)
+    pub fn initial_best(&self) -> Option<(usize, State)> {
+        let mut best = None;
+        self.update_best(&mut best);
+        best
+    }
+
+    /// Replaces the best feasible state if this state is feasible and better, returns whether it was replaced
+    pub fn update_best(&self, best: &mut Option<(usize, State)>) -> bool {
+        if !self.is_feasible_quick() {
+            return false;
+        }
+        let obj = self.obj_value();
+        // The full check is only needed for a candidate
+        let better = best.as_ref().is_none_or(|(prev_best, _)| obj < *prev_best) && self.is_feasible(true);
+        if better {
+            *best = Some((obj, self.clone()));
+        }
+
+        better
     }
 
     pub fn calc_penalty_from_scratch(&self) -> usize {
+        self.penalty_with(&self.penalty_weights)
+    }
+
+    // Penalty under other weights, e.g. to compare states with different weights (O(n))
+    pub fn penalty_with(&self, weights: &PenaltyWeights) -> usize {
         let mut penalty = 0;
         // Uncovered tasks
         for res in 0..self.instance.resources() {
@@ -174,7 +254,7 @@ impl State {
                         } else {
                             // Partially covered
                             task.end() - cover_limit
-                        }).to_penalty(&self.instance, self.penalty_multi);
+                        }).to_penalty(&self.instance, weights);
                     },
                     _ => cover_limit = *time + self.instance.time_regular()
                 };
@@ -183,12 +263,12 @@ impl State {
 
         // Unassigned tasks
         for (task_id, _) in self.assigned_tasks.iter().enumerate().filter(|(_, b)| !*b) {
-            penalty += PenaltyToken::Task(task_id).to_penalty(&self.instance, self.penalty_multi);
+            penalty += PenaltyToken::Task(task_id).to_penalty(&self.instance, weights);
         }
         
         // Unassigned maj maintenances
         for _ in self.assigned_maj_maint.iter().enumerate().filter(|(_, b)| !*b) {
-            penalty += PenaltyToken::MajMaint.to_penalty(&self.instance, self.penalty_multi);
+            penalty += PenaltyToken::MajMaint.to_penalty(&self.instance, weights);
         }
 
         penalty
@@ -250,11 +330,10 @@ impl State {
                         tasks.set(*i, true);
 
                         // Check coverage + uncovered assignment
-                        if *time > self.instance.time_regular() && self.has_maint_covered(res, *time).is_none() {
-                            if requires_completeness || !self.uncovered[res].contains(time) {
-                                eprintln!("error in coverage and uncovered assignments");
-                                return false; 
-                            }
+                        if *time > self.instance.time_regular() && self.has_maint_covered(res, *time).is_none()
+                            && (requires_completeness || !self.uncovered[res].contains(time)) {
+                            eprintln!("error in coverage and uncovered assignments");
+                            return false; 
                         }
                     }
                 }
@@ -282,6 +361,19 @@ impl State {
         !overlap_before && !overlap_after
     }
 
+    // Checks if a maintenance ending at end_time fits into the horizon without overlapping anything
+    pub fn can_add_maintenance(&self, res: usize, end_time: usize, is_mm: bool) -> bool {
+        let length = if is_mm { self.instance.duration_major() } else { self.instance.duration_regular() };
+        if end_time < length || end_time > self.instance.horizon() {
+            return false;
+        }
+        if is_mm && (self.assigned_maj_maint[res] || !self.get_other_mm_overlaps(res, end_time).is_empty()) {
+            return false;
+        }
+
+        self.get_overlaps(res, end_time - length, end_time).is_empty()
+    }
+
     pub fn add_task(&mut self, res: usize, task_id: usize) {
         self.assigned_tasks.set(task_id, true);
         self.task_ass[task_id] = res;
@@ -289,11 +381,11 @@ impl State {
         self.jobs[res].insert(task.end(), JobToken::Task(task_id));
 
         // Update penalties
-        self.penalty_value -= PenaltyToken::Task(task_id).to_penalty(&self.instance, self.penalty_multi);
+        self.penalty_value -= PenaltyToken::Task(task_id).to_penalty(&self.instance, &self.penalty_weights);
         // Uncovered penalties
         if task.end() <= self.instance.time_regular(){ return; }    // All covered in first timeframe
 
-        let cover_limit = match self.jobs[res].range(..task.start()+1).filter(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint).next_back() {
+        let cover_limit = match self.jobs[res].range(..task.start()+1).rfind(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint) {
             Some((time, _)) => *time,
             None => 0
         } + self.instance.time_regular();
@@ -301,7 +393,7 @@ impl State {
         if cover_limit >= task.end() { return; }    // All covered
         let additional_penalty = cmp::min(task.end() - cover_limit, task.length());
         self.uncovered[res].insert(task.end());
-        self.penalty_value += PenaltyToken::RegMaintNotCovered(additional_penalty).to_penalty(&self.instance, self.penalty_multi);
+        self.penalty_value += PenaltyToken::RegMaintNotCovered(additional_penalty).to_penalty(&self.instance, &self.penalty_weights);
     }
 
     pub fn remove_task(&mut self, task_id: usize) {
@@ -314,7 +406,7 @@ impl State {
         // Update penalties
         if self.uncovered[res].contains(&end_time) {
             self.uncovered[res].remove(&end_time);
-            let cover_limit = match self.jobs[res].range(..end_time).filter(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint).next_back() {
+            let cover_limit = match self.jobs[res].range(..end_time).rfind(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint) {
                     Some((time, _)) => *time,
                     None => 0
             } + self.instance.time_regular();
@@ -324,10 +416,10 @@ impl State {
             } else {
                 task.end() - cover_limit
             };
-            self.penalty_value -= PenaltyToken::RegMaintNotCovered(previously_uncovered).to_penalty(&self.instance, self.penalty_multi);
+            self.penalty_value -= PenaltyToken::RegMaintNotCovered(previously_uncovered).to_penalty(&self.instance, &self.penalty_weights);
             // Task was uncovered, remove penalty for it
         }
-        self.penalty_value += PenaltyToken::Task(task_id).to_penalty(&self.instance, self.penalty_multi);
+        self.penalty_value += PenaltyToken::Task(task_id).to_penalty(&self.instance, &self.penalty_weights);
     }
 
     pub fn add_major_maintenance(&mut self, res: usize, end_time: usize) {
@@ -337,7 +429,7 @@ impl State {
         self.jobs[res].insert(end_time, JobToken::MajMaint);
         
         self.update_changes_maint_added(start_time, end_time, res);
-        self.penalty_value -= PenaltyToken::MajMaint.to_penalty(&self.instance, self.penalty_multi);
+        self.penalty_value -= PenaltyToken::MajMaint.to_penalty(&self.instance, &self.penalty_weights);
     }
 
     pub fn remove_major_maintenance(&mut self, res: usize) {
@@ -349,7 +441,7 @@ impl State {
 
         self.update_changes_maint_removed(start_time, end_time, res);
 
-        self.penalty_value += PenaltyToken::MajMaint.to_penalty(&self.instance, self.penalty_multi);
+        self.penalty_value += PenaltyToken::MajMaint.to_penalty(&self.instance, &self.penalty_weights);
     }
 
     pub fn add_regular_maintenance(&mut self, res: usize, end_time: usize) {
@@ -368,27 +460,62 @@ impl State {
         self.update_changes_maint_removed(start_time, end_time, res);
     }
     
-    pub fn repair(&mut self) -> Vec<ChangeToken> {
+    // Reverts the changes of a move (in reverse order)
+    pub fn undo_changes(&mut self, tokens: &[ChangeToken]) {
+        for token in tokens.iter().rev() {
+            match token {
+                ChangeToken::MovedRM(res, prev, new) => {
+                    self.remove_regular_maintenance(*res, *new);
+                    self.add_regular_maintenance(*res, *prev);
+                },
+                ChangeToken::AddRM(res, new_rm) => self.remove_regular_maintenance(*res, *new_rm),
+                ChangeToken::RemoveRM(res, time) => self.add_regular_maintenance(*res, *time),
+                ChangeToken::MovedMM(res, prev) => {
+                    self.remove_major_maintenance(*res);
+                    self.add_major_maintenance(*res, *prev);
+                },
+                ChangeToken::AddMM(res) => self.remove_major_maintenance(*res),
+                ChangeToken::RemoveMM(res, time) => self.add_major_maintenance(*res, *time),
+                ChangeToken::AddTask(task_id) => self.remove_task(*task_id),
+                ChangeToken::RemoveTask(res, task_id) => self.add_task(*res, *task_id),
+            }
+        }
+    }
+
+    pub fn repair(&mut self, rng: &mut StdRng) -> Vec<ChangeToken> {
+        let mut change_tokens = self.repair_maj_maints(rng);
+        change_tokens.append(&mut self.repair_tasks(rng));
+        change_tokens.append(&mut self.repair_coverage(rng));
+
+        change_tokens
+    }
+
+    // Adds unassigned major maintenances where they fit without destruction
+    pub fn repair_maj_maints(&mut self, rng: &mut StdRng) -> Vec<ChangeToken> {
         let mut change_tokens = Vec::new();
-        // Try repair MM
         let mut res_order = (0..self.instance().resources()).collect::<Vec<usize>>();
-        res_order.shuffle(&mut thread_rng());
+        res_order.shuffle(rng);
         for res in 0..self.instance.resources() {
             if self.assigned_maj_maint[res] { continue; }
 
-            let new_time = self.can_add_mm_without_destruction(res);
+            let new_time = self.can_add_mm_without_destruction(res, rng);
             if new_time.is_none() { continue; } // Cannot repair
 
             self.add_major_maintenance(res, new_time.unwrap());
             change_tokens.push(ChangeToken::AddMM(res));
         }
 
-        // Try repair Tasks
+        change_tokens
+    }
+
+    // Adds unassigned tasks to the first free resource (in random order)
+    pub fn repair_tasks(&mut self, rng: &mut StdRng) -> Vec<ChangeToken> {
+        let mut change_tokens = Vec::new();
         for task_id in 0..self.instance.tasks().len() {
             if self.assigned_tasks[task_id] { continue; }
 
             let mut order = (0..self.instance().resources()).collect::<Vec<usize>>();
-            order.shuffle(&mut thread_rng());
+            order.shuffle(rng);
             for r in order {
                 if self.can_add_task(r, task_id) {
                     self.add_task(r, task_id);
@@ -398,14 +525,19 @@ impl State {
             }
         }
 
-        // Try cover
+        change_tokens
+    }
+
+    // Covers uncovered tasks with new regular maintenances
+    pub fn repair_coverage(&mut self, rng: &mut StdRng) -> Vec<ChangeToken> {
+        let mut change_tokens = Vec::new();
         for res in 0..self.instance.resources() {
             let mut last_maint = usize::MAX;
             let mut to_add = Vec::new();
             for time in self.uncovered[res].iter().rev() {
                 if *time > last_maint { continue; } // Already covered in previous step
 
-                let new_rm = self.find_reg_maint_cover_random(res, *time);
+                let new_rm = self.find_reg_maint_cover_random(res, *time, rng);
                 if new_rm.is_none() { continue; }   // Cannot cover task
 
                 let new_rm = new_rm.unwrap();
@@ -456,10 +588,7 @@ impl State {
             return Some(0);
         }
         let limit = time - self.instance.time_regular();
-        match self.jobs[res].range(limit..time).find(|x| x.1 == &JobToken::MajMaint || x.1 == &JobToken::RegMaint) {
-            Some(x) => Some(*x.0),
-            None => None
-        }
+        self.jobs[res].range(limit..time).find(|x| x.1 == &JobToken::MajMaint || x.1 == &JobToken::RegMaint).map(|x| *x.0)
     }
 
     // Add reg maintenance greedily at first suitable position
@@ -483,14 +612,13 @@ impl State {
     }
 
     // Add reg maintenance a random (but covering) position
-    pub fn find_reg_maint_cover_random(&self, res: usize, time: usize) -> Option<usize> {
+    pub fn find_reg_maint_cover_random(&self, res: usize, time: usize, rng: &mut StdRng) -> Option<usize> {
         let first_possible_end = cmp::max(time as isize - self.instance.time_regular() as isize, self.instance.duration_regular() as isize) as usize;
         let windows = self.get_all_suitable_windows_on_res(res, first_possible_end, time, self.instance.duration_regular(), false);
         if windows.is_empty() {
             return None;
         }
-        let mut rng = thread_rng();
-        let (left, right) = windows.choose(&mut rng).unwrap();
+        let (left, right) = windows.choose(rng).unwrap();
         let selected = rng.gen_range(*left..*right+1);
         
         Some(selected)
@@ -538,8 +666,7 @@ impl State {
         let mut windows_for_mm = Vec::new();
         for window in possible_windows.into_iter() {
             let mut splits = vec![window];
-            while !splits.is_empty() {
-                let (mut left, mut right) = splits.pop().unwrap();
+            while let Some((mut left, mut right)) = splits.pop() {
                 let mut can_add = true;
                 for other_res in 0..self.instance.resources() {
                     if other_res == res {
@@ -618,25 +745,24 @@ impl State {
     }
 
     // (time)
-    pub fn can_add_mm_without_destruction(&self, res: usize) -> Option<usize> {
+    pub fn can_add_mm_without_destruction(&self, res: usize, rng: &mut StdRng) -> Option<usize> {
         let length = self.instance().duration_major();
         let windows = self.get_all_suitable_windows_on_res(res, length, self.instance().horizon(), length, true);
         if windows.is_empty() { return None; } // Cannot move selected MM
 
         // Get new random time and add MM
-        let mut rng = thread_rng();
-        let (left, right) = windows.choose(&mut rng).unwrap();
+        let (left, right) = windows.choose(rng).unwrap();
 
         Some(rng.gen_range(*left..*right+1))
     }
 
     // (res, time)
-    pub fn get_rand_rm(&self) -> Option<(usize, usize)> {
+    pub fn get_rand_rm(&self, rng: &mut StdRng) -> Option<(usize, usize)> {
         let num_rm = self.reg_maint_ends.iter().flatten().count();
         if num_rm == 0 {
             return None;
         }
-        let rm_idx = thread_rng().gen_range(0..num_rm);
+        let rm_idx = rng.gen_range(0..num_rm);
         let mut counter = 0;
         for res in 0..self.instance.resources() {
             for rm in self.reg_maint_ends[res].iter() {
@@ -651,52 +777,52 @@ impl State {
     }
 
     // (res, time)
-    pub fn get_rand_mm(&self) -> Option<(usize, usize)> {
+    pub fn get_rand_mm(&self, rng: &mut StdRng) -> Option<(usize, usize)> {
         let num_assigned = self.assigned_maj_maint.iter().filter(|b| *b).count();
         if num_assigned == 0 {
             return None;
         }
-        let res = self.assigned_maj_maint.iter().enumerate().filter(|(_, b)| *b).skip(thread_rng().gen_range(0..num_assigned)).next().unwrap().0;
+        let res = self.assigned_maj_maint.iter().enumerate().filter(|(_, b)| *b).nth(rng.gen_range(0..num_assigned)).unwrap().0;
         Some((res, self.maj_maint_ends[res]))
     }    
 
     // (res)
-    pub fn get_rand_unassigned_mm(&self) -> Option<usize> {
+    pub fn get_rand_unassigned_mm(&self, rng: &mut StdRng) -> Option<usize> {
         let num_unassigned = self.assigned_maj_maint.iter().filter(|b| !*b).count();
         if num_unassigned == 0 {
             return None;
         }
         
-        Some(self.assigned_maj_maint.iter().enumerate().filter(|(_, b)| !*b).skip(thread_rng().gen_range(0..num_unassigned)).next().unwrap().0)
+        Some(self.assigned_maj_maint.iter().enumerate().filter(|(_, b)| !*b).nth(rng.gen_range(0..num_unassigned)).unwrap().0)
     }
 
     // taskid
-    pub fn get_rand_unassigned_task(&self) -> Option<usize> {
+    pub fn get_rand_unassigned_task(&self, rng: &mut StdRng) -> Option<usize> {
         let num_unassigned = self.assigned_tasks.iter().filter(|b| !*b).count();
         if num_unassigned == 0 {
             return None;
         }
         
-        Some(self.assigned_tasks.iter().enumerate().filter(|(_, b)| !*b).skip(thread_rng().gen_range(0..num_unassigned)).next().unwrap().0)
+        Some(self.assigned_tasks.iter().enumerate().filter(|(_, b)| !*b).nth(rng.gen_range(0..num_unassigned)).unwrap().0)
     }
 
     // (res, taskid)
-    pub fn get_rand_assigned_task(&self) -> Option<(usize, usize)> {
+    pub fn get_rand_assigned_task(&self, rng: &mut StdRng) -> Option<(usize, usize)> {
         let num_assigned = self.assigned_tasks.iter().filter(|b| *b).count();
         if num_assigned == 0 {
             return None;
         }
-        let task_id = self.assigned_tasks.iter().enumerate().filter(|(_, b)| *b).skip(thread_rng().gen_range(0..num_assigned)).next().unwrap().0;
+        let task_id = self.assigned_tasks.iter().enumerate().filter(|(_, b)| *b).nth(rng.gen_range(0..num_assigned)).unwrap().0;
         Some((self.task_ass[task_id], task_id))
     }
 
     // (res, time)
-    pub fn get_rand_uncovered_task(&self) -> Option<(usize, usize)> {
+    pub fn get_rand_uncovered_task(&self, rng: &mut StdRng) -> Option<(usize, usize)> {
         let num_uncovered = self.uncovered.iter().flatten().count();
         if num_uncovered == 0 {
             return None;
         }
-        let idx = thread_rng().gen_range(0..num_uncovered);
+        let idx = rng.gen_range(0..num_uncovered);
         let mut counter = 0;
         for res in 0..self.instance.resources() {
             for time in self.uncovered[res].iter() {
@@ -764,7 +890,7 @@ impl State {
         }
         // Update all tasks that were uncovered and are effected
         let prev_maint_limit = match self.jobs[res].range(..end_time)
-            .filter(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint).next_back() 
+            .rfind(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint) 
         {
             Some((t, _)) => *t,
             None => 0
@@ -782,7 +908,7 @@ impl State {
                 cover_limit - task.start()
             } - covered_by_prev;
             
-            self.penalty_value -= PenaltyToken::RegMaintNotCovered(newly_covered_time).to_penalty(&self.instance, self.penalty_multi);
+            self.penalty_value -= PenaltyToken::RegMaintNotCovered(newly_covered_time).to_penalty(&self.instance, &self.penalty_weights);
             if *time <= cover_limit {
                 self.uncovered[res].remove(time);
             }
@@ -842,7 +968,7 @@ impl State {
         }
         // Update all tasks that were uncovered and are effected
         let prev_maint_limit = match self.jobs[res].range(..end_time)
-            .filter(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint).next_back() 
+            .rfind(|(_, job)| **job == JobToken::MajMaint || **job == JobToken::RegMaint) 
         {
             Some((t, _)) => *t,
             None => 0
@@ -859,7 +985,7 @@ impl State {
             } else {
                 cover_limit - task.start()
             } - covered_by_prev;
-            self.penalty_value += PenaltyToken::RegMaintNotCovered(previously_covered_time).to_penalty(&self.instance, self.penalty_multi);
+            self.penalty_value += PenaltyToken::RegMaintNotCovered(previously_covered_time).to_penalty(&self.instance, &self.penalty_weights);
             if *time <= cover_limit {
                 self.uncovered[res].insert(*time);
             }
@@ -0,0 +1,216 @@
+use rand::rngs::StdRng;
+use std::{collections::HashMap, mem};
+
+use super::{adaptive_weights::MoveOutcome, neighborhood::{ChangeToken, Neighborhood}, simulated_annealing::Termination, state::State};
+
+
+/// Parameters of the tabu search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TSParameters {
+    pub candidates: usize,  // Sampled moves per iteration
+    pub tenure: usize       // Iterations an attribute removed by a move stays tabu
+}
+
+/// Part of a solution a move can create or remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TabuAttribute {
+    Task(usize, usize),     // (task_id, res)
+    RegMaint(usize, usize), // (res, end time)
+    MajMaint(usize, usize)  // (res, end time)
+}
+
+/// Tabu search over sampled candidate moves of the operators: every iteration the best admissible candidate is made,
+/// the attributes it removed may not be created again for `tenure` iterations unless that yields a new best state
+pub struct TabuSearch {
+    parameters: TSParameters,
+    neighborhood: Neighborhood,
+    tabu: HashMap<TabuAttribute, usize>,    // Attribute -> iteration until which it is tabu
+    iterations: usize,
+    iterations_since_improvement: usize,
+    best_obj: usize,    // Best working objective (including penalties)
+    best_feasible: Option<(usize, State)>
+}
+
+// Candidate move, replayed from its operator and the generator state it was sampled with
+struct Candidate {
+    operator: usize,
+    rng: StdRng,
+    delta: f64,
+    admissible: bool,   // Changes something and is not tabu (or reaches a new best state)
+    removed: Vec<TabuAttribute>
+}
+
+impl TabuSearch {
+    pub fn new(neighborhood: Neighborhood, parameters: TSParameters) -> Self {
+        let best_feasible = neighborhood.state().initial_best();
+
+        TabuSearch {
+            parameters,
+            neighborhood,
+            tabu: HashMap::new(),
+            iterations: 0,
+            iterations_since_improvement: 0,
+            best_obj: usize::MAX,
+            best_feasible
+        }
+    }
+
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighborhood
+    }
+
+    pub fn get_best(&self) -> &Option<(usize, State)> {
+        &self.best_feasible
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn iterations_since_improvement(&self) -> usize {
+        self.iterations_since_improvement
+    }
+
+    pub fn solve(&mut self, termination: Termination) -> usize {
+        while !termination.is_reached(self.iterations) {
+            self.step();
+        }
+
+        self.iterations
+    }
+
+    fn step(&mut self) {
+        self.iterations += 1;
+        self.iterations_since_improvement += 1;
+
+        let candidates = self.sample();
+        self.make_best(candidates);
+    }
+
+    // Sample candidates, every move is rolled back right away and decided on once all are known
+    fn sample(&mut self) -> Vec<Candidate> {
+        let mut candidates = Vec::with_capacity(self.parameters.candidates);
+        for _ in 0..self.parameters.candidates {
+            let operator = self.neighborhood.select_operator();
+            let rng = self.neighborhood.rng().clone();
+            let delta = self.neighborhood.apply(operator);
+            let mut candidate = Candidate { operator, rng, delta, admissible: false, removed: Vec::new() };
+            if !self.neighborhood.last_changes().is_empty() {
+                let (created, removed) = attributes(self.neighborhood.state(), self.neighborhood.last_changes());
+                let is_tabu = created.iter().any(|attribute| self.tabu.get(attribute).is_some_and(|until| *until >= self.iterations));
+                // Aspiration: tabu moves are allowed if they lead to a new best state
+                let aspiration = self.neighborhood.state().working_obj_val() < self.best_obj;
+                candidate.admissible = !is_tabu || aspiration;
+                candidate.removed = removed;
+            }
+            self.neighborhood.discard();
+            candidates.push(candidate);
+        }
+
+        candidates
+    }
+
+    // Make the admissible candidate with the lowest delta, all others are rejected
+    fn make_best(&mut self, mut candidates: Vec<Candidate>) {
+        let mut best: Option<usize> = None;
+        for (i, candidate) in candidates.iter().enumerate() {
+            if candidate.admissible && best.is_none_or(|best| candidate.delta < candidates[best].delta) {
+                best = Some(i);
+            }
+        }
+        for (i, candidate) in candidates.iter().enumerate() {
+            if Some(i) != best {
+                self.neighborhood.record_rejected(candidate.operator);
+            }
+        }
+
+        let candidate = match best {
+            Some(i) => candidates.swap_remove(i),
+            None => return
+        };
+        // Replay the chosen move, its call was counted when it was sampled. The generator continues after the last
+        // sample, the next iteration must not sample the same moves again.
+        let rng = mem::replace(self.neighborhood.rng(), candidate.rng);
+        let delta = self.neighborhood.replay(candidate.operator);
+        *self.neighborhood.rng() = rng;
+        debug_assert_eq!(delta, candidate.delta);
+        let obj_val = self.neighborhood.state().working_obj_val();
+        let outcome = if obj_val < self.best_obj {
+            MoveOutcome::NewBest
+        } else if delta < 0.0 {
+            MoveOutcome::Improved
+        } else {
+            MoveOutcome::Accepted
+        };
+        self.neighborhood.accept(outcome);
+        for attribute in candidate.removed.into_iter() {
+            self.tabu.insert(attribute, self.iterations + self.parameters.tenure);
+        }
+        if self.iterations.is_multiple_of(1000) {
+            let iterations = self.iterations;
+            self.tabu.retain(|_, until| *until >= iterations);
+        }
+
+        if obj_val < self.best_obj {
+            self.best_obj = obj_val;
+            self.iterations_since_improvement = 0;
+        }
+        self.neighborhood.state().update_best(&mut self.best_feasible);
+    }
+}
+
+// Attributes created and removed by a move, evaluated on the state after the move
+fn attributes(state: &State, changes: &[ChangeToken]) -> (Vec<TabuAttribute>, Vec<TabuAttribute>) {
+    let mut created = Vec::new();
+    let mut removed = Vec::new();
+    for change in changes.iter() {
+        match *change {
+            ChangeToken::MovedRM(res, prev, new) => {
+                created.push(TabuAttribute::RegMaint(res, new));
+                removed.push(TabuAttribute::RegMaint(res, prev));
+            },
+            ChangeToken::AddRM(res, time) => created.push(TabuAttribute::RegMaint(res, time)),
+            ChangeToken::RemoveRM(res, time) => removed.push(TabuAttribute::RegMaint(res, time)),
+            ChangeToken::MovedMM(res, prev) => {
+                created.push(TabuAttribute::MajMaint(res, state.maj_maint_ends()[res]));
+                removed.push(TabuAttribute::MajMaint(res, prev));
+            },
+            ChangeToken::AddMM(res) => created.push(TabuAttribute::MajMaint(res, state.maj_maint_ends()[res])),
+            ChangeToken::RemoveMM(res, time) => removed.push(TabuAttribute::MajMaint(res, time)),
+            ChangeToken::AddTask(task_id) => created.push(TabuAttribute::Task(task_id, state.task_ass()[task_id])),
+            ChangeToken::RemoveTask(res, task_id) => removed.push(TabuAttribute::Task(task_id, res))
+        }
+    }
+
+    (created, removed)
+}
+
+impl Default for TSParameters {
+    fn default() -> Self {
+        TSParameters { candidates: 20, tenure: 30 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use std::path::Path;
+
+    use super::*;
+    use crate::{neighborhood::NeighborhoodConfig, Instance};
+
+    #[test]
+    fn iterations_sample_new_moves() {
+        let instance = Instance::new_from_file(&Path::new(env!("CARGO_MANIFEST_DIR")).join("instances").join("mdp-3-7-5.json")).unwrap();
+        let neighborhood = Neighborhood::new(instance, StdRng::seed_from_u64(1), &NeighborhoodConfig::default());
+        let mut ts = TabuSearch::new(neighborhood, TSParameters::default());
+        let mut previous: Vec<StdRng> = Vec::new();
+        for _ in 0..20 {
+            ts.iterations += 1;
+            let candidates = ts.sample();
+            assert!(candidates.iter().all(|candidate| !previous.contains(&candidate.rng)));
+            previous = candidates.iter().map(|candidate| candidate.rng.clone()).collect();
+            ts.make_best(candidates);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt;
+
+use crate::{instance::Instance, solution::Solution};
+
+
+/// A single constraint violation found by `validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    ResourceCount { expected: usize, found: usize },
+    UnknownTask { res: usize, task_id: usize },
+    TaskAssignedTwice { task_id: usize, first_res: usize, second_res: usize },
+    TaskUnassigned { task_id: usize },
+    MissingMajMaint { res: usize },
+    OutsideHorizon { res: usize, job: Job },
+    Overlap { res: usize, first: Job, second: Job },
+    MajMaintOverlap { first_res: usize, first_end: usize, second_res: usize, second_end: usize },
+    TaskNotCovered { res: usize, task_id: usize, uncovered: usize },
+    ObjectiveMismatch { reported: usize, computed: usize }
+}
+
+/// A job on a resource as interval [start, end)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    Task { id: usize, start: usize, end: usize },
+    RegMaint { start: usize, end: usize },
+    MajMaint { start: usize, end: usize }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    objective: usize,
+    violations: Vec<Violation>
+}
+
+/// Independently checks a solution against the instance and recomputes its objective.
+pub fn validate(instance: &Instance, solution: &Solution) -> ValidationReport {
+    let mut violations = Vec::new();
+    let schedules = solution.resources();
+    if schedules.len() != instance.resources() {
+        violations.push(Violation::ResourceCount { expected: instance.resources(), found: schedules.len() });
+    }
+
+    // Task assignments
+    let mut task_ass = vec![None; instance.tasks().len()];
+    for (res, schedule) in schedules.iter().enumerate() {
+        for &task_id in schedule.tasks().iter() {
+            match task_ass.get(task_id) {
+                None => violations.push(Violation::UnknownTask { res, task_id }),
+                Some(Some(first_res)) => violations.push(Violation::TaskAssignedTwice { task_id, first_res: *first_res, second_res: res }),
+                Some(None) => task_ass[task_id] = Some(res)
+            }
+        }
+    }
+    for (task_id, _) in task_ass.iter().enumerate().filter(|(_, res)| res.is_none()) {
+        violations.push(Violation::TaskUnassigned { task_id });
+    }
+
+    // Jobs per resource, sorted by start time
+    let mut maintenances = Vec::new();
+    let mut maj_maints = Vec::new();
+    for (res, schedule) in schedules.iter().enumerate() {
+        let mut jobs = Vec::new();
+        match schedule.major_maintenance_end() {
+            Some(end) => {
+                maj_maints.push((res, end));
+                jobs.push(maintenance(end, instance.duration_major(), true));
+            },
+            None => violations.push(Violation::MissingMajMaint { res })
+        }
+        for &end in schedule.regular_maintenance_ends().iter() {
+            jobs.push(maintenance(end, instance.duration_regular(), false));
+        }
+        for &id in schedule.tasks().iter().filter(|id| **id < instance.tasks().len()) {
+            let task = &instance.tasks()[id];
+            jobs.push(Job::Task { id, start: task.start(), end: task.end() });
+        }
+        for job in jobs.iter().filter(|job| job.outside_horizon(instance)) {
+            violations.push(Violation::OutsideHorizon { res, job: *job });
+        }
+        jobs.sort_by_key(|job| (job.start(), job.end()));
+
+        // Overlaps (compare against the job reaching furthest so far)
+        let mut furthest: Option<Job> = None;
+        for job in jobs.iter() {
+            if let Some(prev) = furthest {
+                if job.start() < prev.end() {
+                    violations.push(Violation::Overlap { res, first: prev, second: *job });
+                }
+                if job.end() <= prev.end() {
+                    continue;
+                }
+            }
+            furthest = Some(*job);
+        }
+
+        // Coverage by the last maintenance that ended before the task started
+        let mut cover_limit = instance.time_regular();
+        for job in jobs.iter() {
+            match *job {
+                Job::Task { id, start, end } => {
+                    if end <= cover_limit { continue; }
+                    let uncovered = if start > cover_limit { end - start } else { end - cover_limit };
+                    violations.push(Violation::TaskNotCovered { res, task_id: id, uncovered });
+                },
+                Job::RegMaint { end, .. } | Job::MajMaint { end, .. } => cover_limit = end + instance.time_regular()
+            }
+        }
+
+        maintenances.extend(jobs.into_iter().filter(|job| !matches!(job, Job::Task { .. })));
+    }
+
+    // Major maintenances of different resources must not overlap
+    for (i, (first_res, first_end)) in maj_maints.iter().enumerate() {
+        for (second_res, second_end) in maj_maints.iter().skip(i+1) {
+            if first_end.abs_diff(*second_end) < instance.duration_major() {
+                violations.push(Violation::MajMaintOverlap { first_res: *first_res, first_end: *first_end, second_res: *second_res, second_end: *second_end });
+            }
+        }
+    }
+
+    let objective = calc_objective(&maintenances);
+    if objective != solution.objective() {
+        violations.push(Violation::ObjectiveMismatch { reported: solution.objective(), computed: objective });
+    }
+
+    ValidationReport { objective, violations }
+}
+
+// Sum over time of the squared number of concurrent maintenances
+fn calc_objective(maintenances: &[Job]) -> usize {
+    let mut changes = maintenances.iter()
+        .flat_map(|job| [(job.start(), 1isize), (job.end(), -1isize)])
+        .collect::<Vec<(usize, isize)>>();
+    changes.sort();
+
+    let mut objective = 0;
+    let mut count = 0;
+    let mut prev = 0;
+    for (time, change) in changes.into_iter() {
+        objective += count * count * (time - prev);
+        count = (count as isize + change) as usize;
+        prev = time;
+    }
+
+    objective
+}
+
+fn maintenance(end: usize, length: usize, is_mm: bool) -> Job {
+    let start = end.saturating_sub(length);
+    if is_mm { Job::MajMaint { start, end } } else { Job::RegMaint { start, end } }
+}
+
+impl Job {
+    pub fn start(&self) -> usize {
+        match *self {
+            Job::Task { start, .. } | Job::RegMaint { start, .. } | Job::MajMaint { start, .. } => start
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        match *self {
+            Job::Task { end, .. } | Job::RegMaint { end, .. } | Job::MajMaint { end, .. } => end
+        }
+    }
+
+    // Only maintenances are bound to the horizon, tasks may end after it
+    fn outside_horizon(&self, instance: &Instance) -> bool {
+        match *self {
+            Job::Task { .. } => false,
+            Job::RegMaint { end, .. } => end < instance.duration_regular() || end > instance.horizon(),
+            Job::MajMaint { end, .. } => end < instance.duration_major() || end > instance.horizon()
+        }
+    }
+}
+
+impl ValidationReport {
+    pub fn objective(&self) -> usize {
+        self.objective
+    }
+
+    pub fn violations(&self) -> &Vec<Violation> {
+        &self.violations
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Job::Task { id, start, end } => write!(f, "task {} [{}, {})", id, start, end),
+            Job::RegMaint { start, end } => write!(f, "regular maintenance [{}, {})", start, end),
+            Job::MajMaint { start, end } => write!(f, "major maintenance [{}, {})", start, end)
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::ResourceCount { expected, found } => write!(f, "solution has {} resources, instance has {}", found, expected),
+            Violation::UnknownTask { res, task_id } => write!(f, "resource {}: unknown task {}", res, task_id),
+            Violation::TaskAssignedTwice { task_id, first_res, second_res } => write!(f, "task {} assigned to resource {} and resource {}", task_id, first_res, second_res),
+            Violation::TaskUnassigned { task_id } => write!(f, "task {} is not assigned", task_id),
+            Violation::MissingMajMaint { res } => write!(f, "resource {}: no major maintenance", res),
+            Violation::OutsideHorizon { res, job } => write!(f, "resource {}: {} outside of horizon", res, job),
+            Violation::Overlap { res, first, second } => write!(f, "resource {}: {} overlaps {}", res, first, second),
+            Violation::MajMaintOverlap { first_res, first_end, second_res, second_end } => write!(f, "major maintenance of resource {} (end {}) overlaps major maintenance of resource {} (end {})", first_res, first_end, second_res, second_end),
+            Violation::TaskNotCovered { res, task_id, uncovered } => write!(f, "resource {}: task {} not covered by a maintenance ({} uncovered)", res, task_id, uncovered),
+            Violation::ObjectiveMismatch { reported, computed } => write!(f, "reported objective {} differs from computed objective {}", reported, computed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two resources: tasks 0 and 1 on resource 0 with a regular maintenance in between that covers task 1, task 2
+    // (ending before time_regular) on resource 1. No maintenances overlap, the objective is 5 + 10 + 10.
+    const INSTANCE: &str = r#"{"resources": 2, "horizon": 100, "duration_regular": 5, "duration_major": 10, "time_regular": 40,
+        "tasks": [{"start": 0, "length": 30}, {"start": 35, "length": 30}, {"start": 10, "length": 20}]}"#;
+    const SOLUTION: &str = r#"{"objective": 25, "penalty": 0, "resources": [
+        {"major_maintenance_end": 75, "regular_maintenance_ends": [35], "tasks": [0, 1]},
+        {"major_maintenance_end": 45, "regular_maintenance_ends": [], "tasks": [2]}]}"#;
+
+    fn violations(solution: &str) -> Vec<Violation> {
+        let instance: Instance = serde_json::from_str(INSTANCE).unwrap();
+        validate(&instance, &serde_json::from_str(solution).unwrap()).violations().clone()
+    }
+
+    #[test]
+    fn accepts_feasible_solution() {
+        let instance: Instance = serde_json::from_str(INSTANCE).unwrap();
+        let report = validate(&instance, &serde_json::from_str(SOLUTION).unwrap());
+
+        assert!(report.is_feasible(), "{:?}", report.violations());
+        assert_eq!(report.objective(), 25);
+    }
+
+    #[test]
+    fn reports_resource_count() {
+        let solution = r#"{"objective": 15, "penalty": 0, "resources": [
+            {"major_maintenance_end": 75, "regular_maintenance_ends": [35], "tasks": [0, 1, 2]}]}"#;
+
+        assert!(violations(solution).contains(&Violation::ResourceCount { expected: 2, found: 1 }));
+    }
+
+    #[test]
+    fn reports_task_assignment() {
+        let unassigned = SOLUTION.replace("\"tasks\": [2]", "\"tasks\": []");
+        assert_eq!(violations(&unassigned), vec![Violation::TaskUnassigned { task_id: 2 }]);
+
+        let twice = SOLUTION.replace("\"tasks\": [2]", "\"tasks\": [2, 1]");
+        assert!(violations(&twice).contains(&Violation::TaskAssignedTwice { task_id: 1, first_res: 0, second_res: 1 }));
+
+        let unknown = SOLUTION.replace("\"tasks\": [2]", "\"tasks\": [2, 7]");
+        assert_eq!(violations(&unknown), vec![Violation::UnknownTask { res: 1, task_id: 7 }]);
+    }
+
+    #[test]
+    fn reports_major_maintenances() {
+        let missing = SOLUTION.replace("\"major_maintenance_end\": 45", "\"major_maintenance_end\": null");
+        assert_eq!(violations(&missing), vec![Violation::MissingMajMaint { res: 1 }, Violation::ObjectiveMismatch { reported: 25, computed: 15 }]);
+
+        // Overlapping by 5, which counts 4 * 5 instead of 2 * 5
+        let overlapping = SOLUTION.replace("\"major_maintenance_end\": 45", "\"major_maintenance_end\": 70");
+        assert_eq!(violations(&overlapping), vec![
+            Violation::MajMaintOverlap { first_res: 0, first_end: 75, second_res: 1, second_end: 70 },
+            Violation::ObjectiveMismatch { reported: 25, computed: 35 }
+        ]);
+    }
+
+    #[test]
+    fn reports_jobs_outside_horizon() {
+        let solution = SOLUTION.replace("\"major_maintenance_end\": 45", "\"major_maintenance_end\": 105");
+        assert!(violations(&solution).contains(&Violation::OutsideHorizon { res: 1, job: Job::MajMaint { start: 95, end: 105 } }));
+    }
+
+    #[test]
+    fn reports_overlap_on_resource() {
+        let solution = SOLUTION.replace("[35]", "[33]");
+        assert_eq!(violations(&solution), vec![Violation::Overlap {
+            res: 0,
+            first: Job::Task { id: 0, start: 0, end: 30 },
+            second: Job::RegMaint { start: 28, end: 33 }
+        }]);
+    }
+
+    #[test]
+    fn reports_uncovered_task() {
+        // Without the regular maintenance, task 1 is covered up to time_regular only
+        let solution = SOLUTION.replace("[35]", "[]");
+        assert_eq!(violations(&solution), vec![
+            Violation::TaskNotCovered { res: 0, task_id: 1, uncovered: 25 },
+            Violation::ObjectiveMismatch { reported: 25, computed: 20 }
+        ]);
+    }
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use mdpsa::{neighborhood::{NeighborhoodConfig, PenaltyWeights}, parallel_tempering::{PTParameters, ParallelTempering}, state::JobToken, Neighborhood, SAParameters, SimulatedAnnealing, State, Termination};
+use rand::{rngs::StdRng, SeedableRng};
+
+
+fn annealing(state: State, verify_every: usize) -> SimulatedAnnealing {
+    let neighborhood = Neighborhood::from_state(state, StdRng::seed_from_u64(1), &NeighborhoodConfig::default());
+    SimulatedAnnealing::new(neighborhood, SAParameters::default().with_verify_every(verify_every))
+}
+
+fn initial_state() -> State {
+    let mut state = State::new(common::load("mdp-3-7-5"), PenaltyWeights::default());
+    state.initialize(&mut StdRng::seed_from_u64(1));
+    state
+}
+
+// Regular maintenance inside the first task of resource 0, a state the incremental updates never produce
+fn corrupted_state() -> State {
+    let mut state = initial_state();
+    let task_id = state.jobs()[0].values()
+        .find_map(|job| match job {
+            JobToken::Task(task_id) if state.instance().tasks()[*task_id].length() > state.instance().duration_regular() => Some(*task_id),
+            _ => None
+        })
+        .unwrap();
+    let end = state.instance().tasks()[task_id].start() + state.instance().duration_regular();
+    state.add_regular_maintenance(0, end);
+    state
+}
+
+#[test]
+fn verifies_every_nth_iteration() {
+    let mut sa = annealing(initial_state(), 100);
+    sa.solve(Termination::Iterations(1000));
+    assert_eq!(sa.verifications(), 10);
+
+    let mut sa = annealing(initial_state(), 0);
+    sa.solve(Termination::Iterations(1000));
+    assert_eq!(sa.verifications(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Inconsistent state after 0 iterations")]
+fn detects_corrupted_state() {
+    annealing(corrupted_state(), 1).solve(Termination::Iterations(1000));
+}
+
+// The other replicas wait for the corrupted one at the end of the round, the panic must not leave them waiting
+#[test]
+#[should_panic(expected = "Inconsistent state after 0 iterations")]
+fn tempering_passes_on_replica_panic() {
+    let replicas = vec![annealing(initial_state(), 1), annealing(corrupted_state(), 1), annealing(initial_state(), 1)];
+    let parameters = PTParameters { replicas: 3, exchange_interval: 100 };
+    ParallelTempering::new(replicas, parameters, StdRng::seed_from_u64(1)).unwrap().solve(Termination::Iterations(1000));
+}
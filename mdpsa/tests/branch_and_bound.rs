@@ -0,0 +1,44 @@
+mod common;
+
+use mdpsa::{branch_and_bound::BranchAndBound, validator, Solution, Termination};
+
+
+// Optima of the smallest instances, proven by the exact solver without a seed
+const OPTIMA: [(&str, usize); 9] = [
+    ("mdp-3-7-10", 2970),
+    ("mdp-3-7-5", 2970),
+    ("mdp-3-7-8", 3270),
+    ("mdp-3-7-d4", 3020),
+    ("mdp-3-7-d7", 2970),
+    ("mdp-3-7-d9", 3390),
+    ("mdp-3-7-u4", 2970),
+    ("mdp-3-7-u7", 3120),
+    ("mdp-3-7-u9", 3750)
+];
+
+#[test]
+fn proves_known_optima() {
+    for (name, optimum) in OPTIMA.iter() {
+        let instance = common::load(name);
+        let mut bb = BranchAndBound::new(instance.clone(), None);
+        bb.solve(Termination::Iterations(1_000_000));
+
+        assert!(bb.is_optimal(), "{}: search did not complete", name);
+        assert_eq!(bb.lower_bound(), Some(*optimum), "{}", name);
+        let (obj, state) = bb.get_best().as_ref().unwrap();
+        assert_eq!(obj, optimum, "{}", name);
+        let report = validator::validate(&instance, &Solution::from_state(state));
+        assert!(report.is_feasible(), "{}: {:?}", name, report.violations());
+        assert_eq!(report.objective(), *optimum, "{}", name);
+    }
+}
+
+#[test]
+fn node_limit_keeps_bound_open() {
+    let mut bb = BranchAndBound::new(common::load("mdp-3-7-d4"), None);
+    bb.solve(Termination::Iterations(100));
+
+    assert!(!bb.is_optimal());
+    assert_eq!(bb.nodes(), 100);
+    assert!(bb.lower_bound().is_some_and(|bound| bound <= 3020));
+}
@@ -0,0 +1,9 @@
+use std::path::Path;
+
+use mdpsa::Instance;
+
+
+/// Bundled instance by name (file name without extension)
+pub fn load(name: &str) -> Instance {
+    Instance::new_from_file(&Path::new(env!("CARGO_MANIFEST_DIR")).join("instances").join(format!("{}.json", name))).unwrap()
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use mdpsa::{milp::{self, Model, ModelError, Sense}, Instance};
+use std::collections::HashMap;
+
+// Two resources, optimum 25: both major maintenances (10 each) and one regular maintenance (5) that covers task 1
+const INSTANCE: &str = r#"{"resources": 2, "horizon": 100, "duration_regular": 5, "duration_major": 10, "time_regular": 40,
+    "tasks": [{"start": 0, "length": 30}, {"start": 35, "length": 30}, {"start": 10, "length": 20}]}"#;
+
+// Optimal schedule with periods of 5: resource 0 runs tasks 0 and 1, its major maintenance in [65, 75) and its regular
+// maintenance in [30, 35), resource 1 runs task 2 and its major maintenance in [35, 45)
+const OPTIMUM: [(&str, i64); 16] = [
+    ("x_t0_r0", 1), ("x_t1_r0", 1), ("x_t2_r1", 1),
+    ("mm_r0_s13", 1), ("rm_r0_s6", 1), ("mm_r1_s7", 1),
+    ("am_r0_p13", 1), ("am_r0_p14", 1), ("ar_r0_p6", 1), ("am_r1_p7", 1), ("am_r1_p8", 1),
+    ("u_p6_k1", 1), ("u_p7_k1", 1), ("u_p8_k1", 1), ("u_p13_k1", 1), ("u_p14_k1", 1)
+];
+
+// Objective value of the assignment (unnamed variables are 0) and the names of the violated constraints
+fn evaluate(model: &Model, assignment: &[(&str, i64)]) -> (i64, Vec<String>) {
+    let values = assignment.iter().cloned().collect::<HashMap<_, _>>();
+    let value = |var: usize| values.get(model.variables()[var].name()).copied().unwrap_or(0);
+    let objective = model.objective().iter().map(|(var, coef)| coef * value(*var)).sum();
+    let violated = model.constraints().iter().filter(|constraint| {
+        let lhs = constraint.terms().iter().map(|(var, coef)| coef * value(*var)).sum::<i64>();
+        match constraint.sense() {
+            Sense::LessEqual => lhs > constraint.rhs(),
+            Sense::GreaterEqual => lhs < constraint.rhs(),
+            Sense::Equal => lhs != constraint.rhs()
+        }
+    }).map(|constraint| constraint.name().to_string()).collect();
+
+    (objective, violated)
+}
+
+
+#[test]
+fn builds_model() {
+    let instance = common::load("mdp-3-7-5");
+    let model = Model::new(&instance, None).unwrap();
+
+    assert_eq!(model.step(), milp::time_step(&instance));
+    assert!(!model.variables().is_empty());
+    assert!(!model.constraints().is_empty());
+    let mut lp = Vec::new();
+    model.write_lp(&mut lp).unwrap();
+    let lp = String::from_utf8(lp).unwrap();
+    assert!(lp.contains("Minimize") && lp.contains("Subject To"));
+    let mut mps = Vec::new();
+    model.write_mps(&mut mps).unwrap();
+    assert!(!mps.is_empty());
+}
+
+#[test]
+fn models_tiny_instance() {
+    let instance: Instance = serde_json::from_str(INSTANCE).unwrap();
+    let model = Model::new(&instance, None).unwrap();
+
+    assert_eq!(model.step(), 5);
+    // 6 assignments, 38 + 40 starts, 40 + 40 running and 40 level variables
+    assert_eq!(model.variables().len(), 204);
+    // 3 + 2 assignments, 80 running, 40 capacity, 20 + 20 overlap and count, 2 coverage constraints
+    assert_eq!(model.constraints().len(), 167);
+    assert!(model.variables().iter().all(|variable| model.variables().iter().filter(|other| other.name() == variable.name()).count() == 1));
+
+    assert_eq!(evaluate(&model, &OPTIMUM), (25, Vec::new()));
+    // Without the regular maintenance task 1 is not covered
+    let uncovered = OPTIMUM.iter().filter(|(name, _)| !["rm_r0_s6", "ar_r0_p6", "u_p6_k1"].contains(name)).cloned().collect::<Vec<_>>();
+    assert_eq!(evaluate(&model, &uncovered), (20, vec!["cover_t1_r0".to_string()]));
+}
+
+#[test]
+fn rejects_step_not_dividing_durations() {
+    let instance = common::load("mdp-3-7-5");
+
+    assert!(matches!(Model::new(&instance, Some(7)), Err(ModelError::InvalidStep { step: 7, .. })));
+    assert!(matches!(Model::new(&instance, Some(0)), Err(ModelError::InvalidStep { step: 0, .. })));
+}